@@ -0,0 +1,302 @@
+use fftwasm::chirp_search::Concentration;
+use fftwasm::image::{
+    export, Channel, Colormap, Component, ExportOptions, Image, ImportOptions, Normalization,
+};
+use fftwasm::peaks::Estimator;
+use fftwasm::signal2d::Signal2D;
+use fftwasm::window::Window;
+use fftwasm::Signal;
+use rustfft::num_complex::Complex;
+use std::process::exit;
+
+const USAGE: &str = "usage: fftwasm image <input> <output> [options]
+       fftwasm peaks <input> [options]
+
+image: transforms a PGM, PPM or PNG image and writes the result.
+
+options:
+  --transform fft|ifft|frac|none   transform to apply (default fft)
+  --order <a> [<b>]                frft order along x and y for frac
+  --channel luminance|red|green|blue
+  --component magnitude|log|phase|real|imag
+  --normalize none|max|minmax
+  --colormap gray|hot|viridis
+  --floor <dB>                     lower end of the log scale (default -80)
+  --shift-input                    origin of the input at its top left corner
+  --shift-output                   origin of the output at its top left corner
+  --plain                          write plain (ASCII) netpbm
+
+peaks: prints the peak positions of a signal read from a text file with
+one sample per line, either a real value or a real and imaginary part.
+
+options:
+  --rate <Hz>                      sample rate (default 1)
+  --start <s>                      time of the first sample (default 0)
+  --order <a>                      also print the peak of the order a domain
+  --chirp                          estimate chirp rate and frequency
+  --count <k>                      list the k strongest spectral peaks
+  --window rectangular|hann|hamming|blackman|blackman-harris|flat-top
+  --estimator parabolic|gaussian|quinn
+  --metrics <h>                    SNR, THD of h harmonics, SINAD, SFDR and ENOB";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    exit(1)
+}
+
+fn parse<T>(value: Option<String>, name: &str, parse: impl Fn(&str) -> Option<T>) -> T {
+    value
+        .as_deref()
+        .and_then(parse)
+        .unwrap_or_else(|| fail(&format!("invalid value for {}", name)))
+}
+
+fn image(mut args: impl Iterator<Item = String>) -> std::io::Result<()> {
+    let input = args.next().unwrap_or_else(|| fail("missing input"));
+    let output = args.next().unwrap_or_else(|| fail("missing output"));
+    let mut transform = "fft".to_string();
+    let mut order = (1.0, 1.0);
+    let mut import = ImportOptions::default();
+    let mut options = ExportOptions::default();
+    let mut plain = false;
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--transform" => transform = parse(args.next(), &arg, |v| Some(v.to_string())),
+            "--order" => {
+                let a = parse(args.next(), &arg, |v| v.parse().ok());
+                let b = match args.peek().and_then(|v| v.parse().ok()) {
+                    Some(b) => {
+                        args.next();
+                        b
+                    }
+                    None => a,
+                };
+                order = (a, b);
+            }
+            "--channel" => {
+                import.channel = parse(args.next(), &arg, |v| match v {
+                    "luminance" => Some(Channel::Luminance),
+                    "red" => Some(Channel::Red),
+                    "green" => Some(Channel::Green),
+                    "blue" => Some(Channel::Blue),
+                    _ => None,
+                })
+            }
+            "--component" => {
+                options.component = parse(args.next(), &arg, |v| match v {
+                    "magnitude" => Some(Component::Magnitude),
+                    "log" => Some(Component::LogMagnitude),
+                    "phase" => Some(Component::Phase),
+                    "real" => Some(Component::Real),
+                    "imag" => Some(Component::Imaginary),
+                    _ => None,
+                })
+            }
+            "--normalize" => {
+                options.normalization = parse(args.next(), &arg, |v| match v {
+                    "none" => Some(Normalization::None),
+                    "max" => Some(Normalization::Max),
+                    "minmax" => Some(Normalization::MinMax),
+                    _ => None,
+                })
+            }
+            "--colormap" => {
+                options.colormap = parse(args.next(), &arg, |v| match v {
+                    "gray" => Some(Colormap::Gray),
+                    "hot" => Some(Colormap::Hot),
+                    "viridis" => Some(Colormap::Viridis),
+                    _ => None,
+                })
+            }
+            "--floor" => options.floor_db = parse(args.next(), &arg, |v| v.parse().ok()),
+            "--shift-input" => import.shift = true,
+            "--shift-output" => options.shift = true,
+            "--plain" => plain = true,
+            _ => fail(&format!("unknown option {}", arg)),
+        }
+    }
+
+    let source = Image::open(&input)?;
+    let (width, height) = (source.width, source.height);
+
+    if width < Signal::MIN_LEN || height < Signal::MIN_LEN {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("image must be at least {0}x{0} pixels", Signal::MIN_LEN),
+        ));
+    }
+
+    let mut signal = Signal2D::new(width, height);
+    let samples = source.to_complex(&import);
+
+    let result = match transform.as_str() {
+        "fft" => {
+            signal.time_mut().copy_from_slice(&samples);
+            signal.update_freq();
+            signal.freq()
+        }
+        "ifft" => {
+            signal.freq_mut().copy_from_slice(&samples);
+            signal.update_time();
+            signal.time()
+        }
+        "frac" => {
+            signal.time_mut().copy_from_slice(&samples);
+            signal.update_frac(order.0, order.1);
+            signal.frac()
+        }
+        "none" => &samples,
+        _ => fail(&format!("unknown transform {}", transform)),
+    };
+
+    let image = export(result, width, height, &options);
+
+    if plain {
+        std::fs::write(&output, image.to_netpbm(true))
+    } else {
+        image.save(&output)
+    }
+}
+
+fn read_samples(path: &str) -> std::io::Result<Vec<Complex<f32>>> {
+    let invalid = |line: usize| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid sample on line {}", line + 1),
+        )
+    };
+
+    std::fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let values: Vec<f32> = line
+                .split_whitespace()
+                .map(|v| v.parse().map_err(|_| invalid(i)))
+                .collect::<Result<_, _>>()?;
+
+            match values[..] {
+                [re] => Ok(Complex::new(re, 0.0)),
+                [re, im] => Ok(Complex::new(re, im)),
+                _ => Err(invalid(i)),
+            }
+        })
+        .collect()
+}
+
+fn peaks(mut args: impl Iterator<Item = String>) -> std::io::Result<()> {
+    let input = args.next().unwrap_or_else(|| fail("missing input"));
+    let mut sample_rate = 1.0;
+    let mut start_time = 0.0;
+    let mut order = None;
+    let mut chirp = false;
+    let mut count = 0;
+    let mut harmonics = None;
+    let mut window = Window::Hann;
+    let mut estimator = Estimator::Gaussian;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rate" => {
+                sample_rate = parse(args.next(), &arg, |v| {
+                    v.parse().ok().filter(|r: &f32| r.is_finite() && *r > 0.0)
+                })
+            }
+            "--start" => start_time = parse(args.next(), &arg, |v| v.parse().ok()),
+            "--order" => order = Some(parse(args.next(), &arg, |v| v.parse().ok())),
+            "--chirp" => chirp = true,
+            "--count" => count = parse(args.next(), &arg, |v| v.parse().ok()),
+            "--metrics" => harmonics = Some(parse(args.next(), &arg, |v| v.parse().ok())),
+            "--window" => {
+                window = parse(args.next(), &arg, |v| match v {
+                    "rectangular" => Some(Window::Rectangular),
+                    "hann" => Some(Window::Hann),
+                    "hamming" => Some(Window::Hamming),
+                    "blackman" => Some(Window::Blackman),
+                    "blackman-harris" => Some(Window::BlackmanHarris),
+                    "flat-top" => Some(Window::FlatTop),
+                    _ => None,
+                })
+            }
+            "--estimator" => {
+                estimator = parse(args.next(), &arg, |v| match v {
+                    "parabolic" => Some(Estimator::Parabolic),
+                    "gaussian" => Some(Estimator::Gaussian),
+                    "quinn" => Some(Estimator::Quinn),
+                    _ => None,
+                })
+            }
+            _ => fail(&format!("unknown option {}", arg)),
+        }
+    }
+
+    let samples = read_samples(&input)?;
+
+    if samples.len() < Signal::MIN_LEN {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("need at least {} samples", Signal::MIN_LEN),
+        ));
+    }
+
+    let mut signal = Signal::new(samples.len());
+    signal.set_sample_rate(sample_rate);
+    signal.set_start_time(start_time);
+    signal.time_mut().copy_from_slice(&samples);
+
+    signal.update_freq();
+    println!("time peak: {} s", signal.time_peak());
+    println!("frequency peak: {} Hz", signal.freq_peak());
+
+    if let Some(order) = order {
+        signal.update_frac(order);
+        println!("order {} peak: {} s", order, signal.frac_peak());
+    }
+
+    signal.set_window(window);
+    signal.update_peaks(count, -80.0, 2.0, estimator);
+
+    for peak in (0..signal.get_peaks_len()).filter_map(|i| signal.get_peak(i)) {
+        println!(
+            "spectral peak: {} Hz, amplitude {}, phase {}",
+            peak.frequency, peak.amplitude, peak.phase
+        );
+    }
+
+    if let Some(harmonics) = harmonics {
+        let metrics = signal.metrics(harmonics);
+        println!("fundamental: {} Hz", metrics.fundamental);
+        println!("snr: {} dB", metrics.snr);
+        println!("thd: {} dB", metrics.thd);
+        println!("sinad: {} dB", metrics.sinad);
+        println!("sfdr: {} dB", metrics.sfdr);
+        println!("enob: {} bits", metrics.enob);
+    }
+
+    if chirp {
+        let estimate = signal.estimate_chirp(Concentration::Entropy);
+        println!("chirp order: {}", estimate.order);
+        println!("chirp rate: {} Hz/s", estimate.chirp_rate);
+        println!("chirp frequency: {} Hz", estimate.frequency);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+
+    let result = match args.next().as_deref() {
+        Some("image") => image(args),
+        Some("peaks") => peaks(args),
+        _ => fail("missing or unknown command"),
+    };
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        exit(1);
+    }
+}
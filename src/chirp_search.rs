@@ -0,0 +1,220 @@
+use crate::Complex;
+use crate::FrftImpl;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// How the concentration of a fractional domain is measured.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Concentration {
+    /// Share of the energy in the strongest sample.
+    Peak,
+    /// Shannon entropy of the normalized energy distribution.
+    Entropy,
+}
+
+impl Concentration {
+    /// Score of `signal`, higher is more concentrated.
+    fn score(self, signal: &[Complex<f32>]) -> f32 {
+        let energy: f32 = signal.iter().map(|v| v.norm_sqr()).sum();
+
+        if energy == 0.0 {
+            return 0.0;
+        }
+
+        match self {
+            Concentration::Peak => signal.iter().map(|v| v.norm_sqr()).fold(0.0, f32::max) / energy,
+            Concentration::Entropy => signal
+                .iter()
+                .map(|v| v.norm_sqr() / energy)
+                .filter(|p| *p > 0.0)
+                .map(|p| p * p.ln())
+                .sum(),
+        }
+    }
+}
+
+/// Chirp parameters estimated from the fractional domain
+/// in which the signal is most concentrated.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct ChirpEstimate {
+    /// Order of the most concentrated fractional domain, in [0, 2).
+    pub order: f32,
+    /// Position of the peak in that domain, in samples from the center.
+    pub position: f32,
+    /// Concentration score of that domain.
+    pub score: f32,
+    /// Chirp rate in Hz per second.
+    pub chirp_rate: f32,
+    /// Frequency in Hz at the center of the signal.
+    pub frequency: f32,
+}
+
+impl ChirpEstimate {
+    /// Convert a peak at `position` of the order `order` domain of a
+    /// signal with `length` samples into chirp rate and frequency.
+    ///
+    /// A peak at position u of the domain with angle alpha = order*pi/2
+    /// belongs to a chirp with rate -cot(alpha) and frequency
+    /// u/sin(alpha), both in the dimensionless units of the FRFT with a
+    /// sample spacing of 1/sqrt(N) in time and frequency. Order 0 (an
+    /// impulse) has an infinite chirp rate.
+    pub fn new(length: usize, sample_rate: f32, order: f32, position: f32, score: f32) -> Self {
+        let alpha = order * PI / 2.0;
+        let f_n = length as f32;
+
+        Self {
+            order,
+            position,
+            score,
+            chirp_rate: -sample_rate * sample_rate / f_n / alpha.tan(),
+            frequency: sample_rate * position / f_n / alpha.sin(),
+        }
+    }
+}
+
+/// Coarse to fine search of the order in [0, 2) in which `signal` is
+/// most concentrated. `buffer` is left holding that fractional domain.
+///
+/// Orders a and a+2 only differ by a mirroring, so their
+/// concentration is the same.
+pub fn find_order(
+    frft: &mut FrftImpl,
+    signal: &[Complex<f32>],
+    buffer: &mut [Complex<f32>],
+    concentration: Concentration,
+) -> (f32, f32) {
+    let mut evaluate = |order: f32| {
+        buffer.copy_from_slice(signal);
+        frft.process_unitary(buffer, order);
+
+        concentration.score(buffer)
+    };
+
+    let mut best = (0.0, f32::NEG_INFINITY);
+    let mut center = 1.0;
+    let mut radius = 1.0;
+
+    for steps in [40, 20, 20] {
+        let step = 2.0 * radius / steps as f32;
+
+        for i in 0..steps {
+            let order = center - radius + i as f32 * step;
+            let score = evaluate(order);
+
+            if score > best.1 {
+                best = (order, score);
+            }
+        }
+
+        center = best.0;
+        radius = step;
+    }
+
+    let order = best.0.rem_euclid(2.0);
+    let score = evaluate(order);
+
+    (order, score)
+}
+
+/// Position of the strongest sample, in samples from the center,
+/// refined by a parabola through the magnitudes of its neighbours.
+pub fn peak_position(signal: &[Complex<f32>]) -> f32 {
+    let n = signal.len();
+    let Some((peak, _)) = signal
+        .iter()
+        .map(|v| v.norm())
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    else {
+        return 0.0;
+    };
+
+    let offset = if peak > 0 && peak + 1 < n {
+        let (l, c, r) = (
+            signal[peak - 1].norm(),
+            signal[peak].norm(),
+            signal[peak + 1].norm(),
+        );
+        let denom = l - 2.0 * c + r;
+
+        if denom != 0.0 {
+            0.5 * (l - r) / denom
+        } else {
+            0.0
+        }
+    } else {
+        0.0
+    };
+
+    peak as f32 + offset - (n / 2) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chirp_search::{find_order, peak_position, ChirpEstimate, Concentration};
+    use crate::Complex;
+    use crate::FrftImpl;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    /// Chirp with `rate` in Hz/s and frequency `freq` in Hz at the center.
+    fn chirp(n: usize, sample_rate: f32, rate: f32, freq: f32) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| {
+                let t = (i as f32 - (n / 2) as f32) / sample_rate;
+                Complex::new(0.0, 2.0 * PI * (freq * t + 0.5 * rate * t * t)).exp()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn peak_position_interpolates() {
+        let signal: Vec<_> = (0..16)
+            .map(|i| {
+                let x = i as f32 - 8.0 - 2.3;
+                Complex::new(f32::exp(-x * x / 8.0), 0.0)
+            })
+            .collect();
+
+        assert_approx_eq!(2.3, peak_position(&signal), 0.05);
+    }
+
+    #[test]
+    fn estimates_chirp_parameters() {
+        let n = 256;
+        let sample_rate = 1000.0;
+
+        for (rate, freq) in [(2000.0, 50.0), (-3000.0, -20.0), (800.0, 0.0)] {
+            let signal = chirp(n, sample_rate, rate, freq);
+
+            for concentration in [Concentration::Peak, Concentration::Entropy] {
+                let mut frft = FrftImpl::new(n);
+                let mut buffer = vec![Complex::default(); n];
+
+                let (order, score) = find_order(&mut frft, &signal, &mut buffer, concentration);
+                let position = peak_position(&buffer);
+                let estimate = ChirpEstimate::new(n, sample_rate, order, position, score);
+
+                assert_approx_eq!(rate, estimate.chirp_rate, 0.03 * rate.abs());
+                assert_approx_eq!(freq, estimate.frequency, 2.0);
+            }
+        }
+    }
+
+    #[test]
+    fn tone_is_found_at_order_one() {
+        let n = 128;
+        let signal = chirp(n, 100.0, 0.0, 12.5);
+        let mut frft = FrftImpl::new(n);
+        let mut buffer = vec![Complex::default(); n];
+
+        let (order, score) = find_order(&mut frft, &signal, &mut buffer, Concentration::Peak);
+        let estimate = ChirpEstimate::new(n, 100.0, order, peak_position(&buffer), score);
+
+        assert_approx_eq!(1.0, order, 1e-3);
+        assert_approx_eq!(0.0, estimate.chirp_rate, 1.0);
+        assert_approx_eq!(12.5, estimate.frequency, 0.1);
+    }
+}
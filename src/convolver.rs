@@ -1,28 +1,38 @@
 use crate::iter_into_slice;
 use crate::Arc;
 use crate::Fft;
-use crate::{Complex, FftPlanner};
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::Complex;
 
 pub struct Convolver {
     fft_conv: Arc<dyn Fft<f32>>,
+    ifft_conv: Arc<dyn Fft<f32>>,
     pad_a: Vec<Complex<f32>>,
     pad_b: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
 }
 
 impl Convolver {
     pub fn new(length: usize) -> Self {
         let mut pad_a = vec![Complex::default(); length];
         let mut pad_b = vec![Complex::default(); length];
-        let mut planner = FftPlanner::new();
-        let fft_conv = planner.plan_fft_forward(length);
+        let fft_conv = plan_fft_forward(length);
+        let ifft_conv = plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_conv.get_inplace_scratch_len(),
+            ifft_conv.get_inplace_scratch_len(),
+        );
+        let mut scratch = vec![Complex::default(); scratch_len];
 
-        fft_conv.process(&mut pad_a);
-        fft_conv.process(&mut pad_b);
+        fft_conv.process_with_scratch(&mut pad_a, &mut scratch);
+        fft_conv.process_with_scratch(&mut pad_b, &mut scratch);
 
         Self {
             fft_conv,
+            ifft_conv,
             pad_a,
             pad_b,
+            scratch,
         }
     }
 
@@ -38,8 +48,10 @@ impl Convolver {
         iter_into_slice(a, &mut self.pad_a);
         iter_into_slice(b, &mut self.pad_b);
 
-        self.fft_conv.process(&mut self.pad_a);
-        self.fft_conv.process(&mut self.pad_b);
+        self.fft_conv
+            .process_with_scratch(&mut self.pad_a, &mut self.scratch);
+        self.fft_conv
+            .process_with_scratch(&mut self.pad_b, &mut self.scratch);
 
         iter_into_slice(
             self.pad_a.iter().zip(self.pad_b.iter()).map(|(a, b)| a * b),
@@ -54,8 +66,7 @@ impl Convolver {
         into: &mut [Complex<f32>],
     ) {
         self.conv_spectral(a, b, into);
-        self.fft_conv.process(into);
-        into.reverse();
+        self.ifft_conv.process_with_scratch(into, &mut self.scratch);
         let scale = self.pad_a.len() as f32;
 
         for r in into.iter_mut() {
@@ -63,8 +74,12 @@ impl Convolver {
         }
     }
 
-    pub fn fft(&self, signal: &mut [Complex<f32>]) {
-        self.fft_conv.process(signal);
+    pub fn fft(&mut self, signal: &mut [Complex<f32>]) {
+        self.fft_conv.process_with_scratch(signal, &mut self.scratch);
+    }
+
+    pub fn ifft(&mut self, signal: &mut [Complex<f32>]) {
+        self.ifft_conv.process_with_scratch(signal, &mut self.scratch);
     }
 }
 
@@ -0,0 +1,186 @@
+use crate::conv_length;
+use crate::Complex;
+use crate::Convolver;
+use std::f64::consts::PI;
+
+/// Chirp-Z transform evaluating the z-transform of a signal
+/// on the spiral contour z_k = A * W^(-k) for k = 0..M
+///
+/// X_k = sum_n x_n A^(-n) W^(nk)
+///
+/// Implemented via Bluestein's identity nk = (n^2 + k^2 - (k-n)^2) / 2
+/// which turns the sum into a convolution with the chirp W^(-j^2/2).
+pub struct ChirpZ {
+    len: usize,
+    points: usize,
+    convolver: Convolver,
+    conv_res: Vec<Complex<f32>>,
+}
+
+impl ChirpZ {
+    pub fn new(length: usize, points: usize) -> Self {
+        let conv_len = conv_length(length, length + points - 1);
+
+        Self {
+            len: length,
+            points,
+            convolver: Convolver::new(conv_len),
+            conv_res: vec![Complex::default(); conv_len],
+        }
+    }
+
+    pub fn points(&self) -> usize {
+        self.points
+    }
+
+    /// Evaluate the z-transform of `input` at `points` locations starting
+    /// at `start` and advancing by the factor `1/ratio` each step.
+    pub fn process(
+        &mut self,
+        input: &[Complex<f32>],
+        output: &mut [Complex<f32>],
+        start: Complex<f32>,
+        ratio: Complex<f32>,
+    ) {
+        let n = self.len;
+        let ln_a = Complex::new(start.re as f64, start.im as f64).ln();
+        let ln_w = Complex::new(ratio.re as f64, ratio.im as f64).ln();
+        let power = |exponent: Complex<f64>| {
+            let z = exponent.exp();
+            Complex::new(z.re as f32, z.im as f32)
+        };
+
+        // a_n = x_n * A^(-n) * W^(n^2/2)
+        let weighted = input.iter().enumerate().map(|(i, x)| {
+            let f_i = i as f64;
+            x * power(-f_i * ln_a + f_i * f_i / 2.0 * ln_w)
+        });
+        // b_j = W^(-(j-N+1)^2/2)
+        let chirp = (0..(n + self.points - 1)).map(|j| {
+            let f_j = j as f64 - (n as f64 - 1.0);
+            power(-f_j * f_j / 2.0 * ln_w)
+        });
+
+        self.convolver.conv(weighted, chirp, &mut self.conv_res);
+
+        // X_k = W^(k^2/2) * (a * b)_(k+N-1)
+        for (k, out) in output.iter_mut().take(self.points).enumerate() {
+            let f_k = k as f64;
+            *out = self.conv_res[k + n - 1] * power(f_k * f_k / 2.0 * ln_w);
+        }
+    }
+
+    /// Evaluate the spectrum of `input` at `points` equally spaced
+    /// frequencies between `from` and `to` (inclusive), measured in bins.
+    ///
+    /// The phase is relative to the center sample of `input`,
+    /// like the spectrum computed by `Signal::update_freq`.
+    pub fn zoom(
+        &mut self,
+        input: &[Complex<f32>],
+        output: &mut [Complex<f32>],
+        from: f32,
+        to: f32,
+    ) {
+        let f_n = self.len as f64;
+        let origin = (self.len / 2) as f64;
+        let step = if self.points > 1 {
+            (to - from) as f64 / (self.points - 1) as f64
+        } else {
+            0.0
+        };
+        let start = Complex::new(0.0, 2.0 * PI * from as f64 / f_n).exp();
+        let ratio = Complex::new(0.0, -2.0 * PI * step / f_n).exp();
+
+        self.process(
+            input,
+            output,
+            Complex::new(start.re as f32, start.im as f32),
+            Complex::new(ratio.re as f32, ratio.im as f32),
+        );
+
+        for (k, out) in output.iter_mut().take(self.points).enumerate() {
+            let f = from as f64 + k as f64 * step;
+            let shift = Complex::new(0.0, 2.0 * PI * f * origin / f_n).exp();
+
+            *out *= Complex::new(shift.re as f32, shift.im as f32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::czt::ChirpZ;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use rustfft::FftPlanner;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn czt_matches_fft() {
+        let n = 12;
+        let signal: Vec<_> = (0..n)
+            .map(|i| Complex::new((i as f32 * 0.7).sin(), (i as f32 * 0.3).cos()))
+            .collect();
+        let mut expected = signal.clone();
+        FftPlanner::new().plan_fft_forward(n).process(&mut expected);
+
+        let mut czt = ChirpZ::new(n, n);
+        let mut result = vec![Complex::default(); n];
+        let ratio = Complex::new(0.0, -2.0 * PI / n as f32).exp();
+        czt.process(&signal, &mut result, Complex::new(1.0, 0.0), ratio);
+
+        for (e, r) in expected.iter().zip(result.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-3);
+            assert_approx_eq!(e.im, r.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn czt_spiral() {
+        let n = 6;
+        let m = 9;
+        let signal: Vec<_> = (0..n).map(|i| Complex::new(1.0 + i as f32, -0.5)).collect();
+        let start = Complex::new(0.9, 0.2);
+        let ratio = Complex::from_polar(1.02, -0.3);
+
+        let mut czt = ChirpZ::new(n, m);
+        let mut result = vec![Complex::default(); m];
+        czt.process(&signal, &mut result, start, ratio);
+
+        for (k, r) in result.iter().enumerate() {
+            let z = start * ratio.powi(-(k as i32));
+            let e: Complex<f32> = signal
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x * z.powi(-(i as i32)))
+                .sum();
+
+            assert_approx_eq!(e.re, r.re, 1e-3);
+            assert_approx_eq!(e.im, r.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn zoom_finds_fractional_peak() {
+        let n = 64;
+        let f = 5.3;
+        let signal: Vec<_> = (0..n)
+            .map(|i| Complex::new(0.0, 2.0 * PI * f * (i as f32 - 32.0) / n as f32).exp())
+            .collect();
+
+        let mut czt = ChirpZ::new(n, 101);
+        let mut result = vec![Complex::default(); 101];
+        czt.zoom(&signal, &mut result, 4.0, 6.0);
+
+        let (peak, value) = result
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.norm().partial_cmp(&b.norm()).unwrap())
+            .unwrap();
+
+        assert_eq!(65, peak);
+        assert_approx_eq!(n as f32, value.re, 1e-2);
+        assert_approx_eq!(0.0, value.im, 1e-2);
+    }
+}
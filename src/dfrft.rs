@@ -0,0 +1,117 @@
+use crate::hermite::HermiteBasis;
+use crate::Complex;
+use std::f32::consts::PI;
+
+/// Discrete fractional Fourier transform by eigen decomposition of the DFT.
+///
+/// The transform of order a multiplies the coefficient of the discrete
+/// Hermite-Gaussian of order k by e^(-i*k*a*pi/2).
+///
+/// Unlike the chirp based `Frft`, which is only accurate for signals well
+/// inside the time-frequency window, it is unitary and index additive
+/// for any input up to rounding errors, at a cost of O(N^2) per transform
+/// and O(N^3) to set up. Orders a and -a are inverse to each other.
+pub struct Dfrft {
+    basis: HermiteBasis,
+    coefficients: Vec<Complex<f32>>,
+}
+
+impl Dfrft {
+    pub fn new(length: usize) -> Self {
+        Self {
+            basis: HermiteBasis::new(length, length),
+            coefficients: vec![Complex::default(); length],
+        }
+    }
+
+    pub fn process(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
+        self.basis.decompose(signal, &mut self.coefficients);
+
+        for (k, c) in self.coefficients.iter_mut().enumerate() {
+            let turns = (self.basis.order(k) as f32 * fraction).rem_euclid(4.0);
+
+            *c *= Complex::new(0.0, -turns * PI / 2.0).exp();
+        }
+
+        self.basis.synthesize(&self.coefficients, signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dfrft::Dfrft;
+    use crate::plan_cache::plan_fft_forward;
+    use crate::utils::fft_centered;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn noise(n: usize) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| {
+                let x = i as f32;
+                Complex::new((x * 12.9898).sin().fract(), (x * 78.233).cos().fract())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn order_one_is_centered_dft() {
+        for n in [16, 17] {
+            let mut dfrft = Dfrft::new(n);
+            let mut signal = noise(n);
+            let mut expected = signal.clone();
+            let fft = plan_fft_forward(n);
+            let mut scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+            fft_centered(&fft, &mut expected, &mut scratch);
+            dfrft.process(&mut signal, 1.0);
+
+            for (e, r) in expected.iter().zip(&signal) {
+                assert_approx_eq!(e.re / f32::sqrt(n as f32), r.re, 1e-4);
+                assert_approx_eq!(e.im / f32::sqrt(n as f32), r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn order_two_is_reflection() {
+        for n in [16, 17] {
+            let mut dfrft = Dfrft::new(n);
+            let expected = noise(n);
+            let mut signal = expected.clone();
+
+            dfrft.process(&mut signal, 2.0);
+
+            for (j, r) in signal.iter().enumerate() {
+                let e = expected[(2 * (n / 2) + n - j) % n];
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn additive_and_invertible() {
+        for n in [32, 33] {
+            let mut dfrft = Dfrft::new(n);
+            let expected = noise(n);
+            let (mut once, mut twice) = (expected.clone(), expected.clone());
+
+            dfrft.process(&mut once, 0.9);
+            dfrft.process(&mut twice, 0.3);
+            dfrft.process(&mut twice, 0.6);
+
+            for (e, r) in once.iter().zip(&twice) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+
+            dfrft.process(&mut once, -0.9);
+
+            for (e, r) in expected.iter().zip(&once) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+}
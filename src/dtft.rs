@@ -0,0 +1,155 @@
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::plan_cache::plan_fft_forward;
+use std::f64::consts::PI;
+
+// All evaluators measure frequencies in bins, i.e. cycles per signal length,
+// and use the center sample as origin, like `Signal::update_freq`.
+
+fn center_phase(n: usize, omega: f64) -> Complex<f64> {
+    Complex::new(0.0, omega * (n / 2) as f64).exp()
+}
+
+/// Evaluate the DTFT of `signal` at a single frequency using
+/// the generalized Goertzel recurrence.
+pub fn goertzel(signal: &[Complex<f32>], freq: f32) -> Complex<f32> {
+    let n = signal.len();
+
+    if n == 0 {
+        return Complex::default();
+    }
+
+    let omega = 2.0 * PI * freq as f64 / n as f64;
+    let coeff = 2.0 * omega.cos();
+    let mut s1 = Complex::<f64>::default();
+    let mut s2 = Complex::<f64>::default();
+
+    for x in signal {
+        let s0 = Complex::new(x.re as f64, x.im as f64) + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+
+    // y = s[N-1] - e^(-iw) s[N-2] = sum x[m] e^(iw(N-1-m))
+    let y = s1 - Complex::new(0.0, -omega).exp() * s2;
+    let result = y * Complex::new(0.0, -omega * (n - 1) as f64).exp() * center_phase(n, omega);
+
+    Complex::new(result.re as f32, result.im as f32)
+}
+
+/// Evaluate the DTFT of `signal` at a single frequency by direct summation.
+pub fn dtft(signal: &[Complex<f32>], freq: f32) -> Complex<f32> {
+    let n = signal.len();
+
+    if n == 0 {
+        return Complex::default();
+    }
+
+    let omega = 2.0 * PI * freq as f64 / n as f64;
+
+    let sum: Complex<f64> = signal
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            Complex::new(x.re as f64, x.im as f64) * Complex::new(0.0, -omega * i as f64).exp()
+        })
+        .sum();
+    let result = sum * center_phase(n, omega);
+
+    Complex::new(result.re as f32, result.im as f32)
+}
+
+/// Samples the DTFT of a signal of length N at K*N equally spaced
+/// frequencies by zero padding, to plot the underlying continuous
+/// spectrum between the bins of the DFT.
+///
+/// Output index j corresponds to the frequency (j - K*N/2) / K bins.
+pub struct ContinuousSpectrum {
+    len: usize,
+    oversampling: usize,
+    fft: Arc<dyn Fft<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl ContinuousSpectrum {
+    pub fn new(length: usize, oversampling: usize) -> Self {
+        let oversampling = oversampling.max(1);
+        let fft = plan_fft_forward(length * oversampling);
+        let scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        Self {
+            len: length,
+            oversampling,
+            fft,
+            scratch,
+        }
+    }
+
+    pub fn oversampling(&self) -> usize {
+        self.oversampling
+    }
+
+    pub fn result_len(&self) -> usize {
+        self.len * self.oversampling
+    }
+
+    pub fn process(&mut self, signal: &[Complex<f32>], output: &mut [Complex<f32>]) {
+        let n = self.len;
+        let padded = self.result_len();
+
+        output.fill(Complex::default());
+
+        // keep the center sample at the origin of the padded signal
+        for (i, x) in signal.iter().enumerate() {
+            output[(i + padded - n / 2) % padded] = *x;
+        }
+
+        self.fft.process_with_scratch(output, &mut self.scratch);
+        output.rotate_right(padded / 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dtft::{dtft, goertzel, ContinuousSpectrum};
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn signal(n: usize) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| Complex::new((i as f32 * 0.37).sin() + 0.5, (i as f32 * 0.11).cos()))
+            .collect()
+    }
+
+    #[test]
+    fn goertzel_matches_dtft() {
+        let x = signal(40);
+
+        for freq in [-7.25, -0.5, 0.0, 1.0, 3.7, 19.9] {
+            let g = goertzel(&x, freq);
+            let d = dtft(&x, freq);
+
+            assert_approx_eq!(d.re, g.re, 1e-3);
+            assert_approx_eq!(d.im, g.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn continuous_matches_dtft() {
+        let x = signal(16);
+        let mut spectrum = ContinuousSpectrum::new(16, 4);
+        let mut result = vec![Complex::default(); spectrum.result_len()];
+
+        spectrum.process(&x, &mut result);
+
+        assert_eq!(64, result.len());
+        for (j, r) in result.iter().enumerate() {
+            let freq = (j as f32 - 32.0) / 4.0;
+            let e = dtft(&x, freq);
+
+            assert_approx_eq!(e.re, r.re, 1e-3);
+            assert_approx_eq!(e.im, r.im, 1e-3);
+        }
+    }
+}
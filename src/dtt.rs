@@ -0,0 +1,509 @@
+use crate::linalg::{symmetric_eigen, tridiagonal_eigen};
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+use wasm_bindgen::prelude::*;
+
+/// The discrete cosine and sine transforms, all in their orthonormal form.
+///
+/// Sample and frequency indices start at 0, there is no centering.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    DctI,
+    DctII,
+    DctIII,
+    DctIV,
+    DstI,
+    DstII,
+    DstIII,
+    DstIV,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 8] = [
+        Kind::DctI,
+        Kind::DctII,
+        Kind::DctIII,
+        Kind::DctIV,
+        Kind::DstI,
+        Kind::DstII,
+        Kind::DstIII,
+        Kind::DstIV,
+    ];
+
+    /// The transform undoing this one. Types I and IV are their own
+    /// inverse, types II and III invert each other.
+    pub fn inverse(self) -> Kind {
+        match self {
+            Kind::DctII => Kind::DctIII,
+            Kind::DctIII => Kind::DctII,
+            Kind::DstII => Kind::DstIII,
+            Kind::DstIII => Kind::DstII,
+            kind => kind,
+        }
+    }
+
+    pub fn is_involution(self) -> bool {
+        self.inverse() == self
+    }
+
+    fn is_sine(self) -> bool {
+        matches!(self, Kind::DstI | Kind::DstII | Kind::DstIII | Kind::DstIV)
+    }
+
+    /// Denominator d and the offsets of sample and frequency index
+    /// of the kernel cos(pi*(n+alpha)*(k+beta)/d) or its sine.
+    fn kernel(self, length: usize) -> (usize, f32, f32) {
+        match self {
+            Kind::DctI => (length - 1, 0.0, 0.0),
+            Kind::DctII => (length, 0.5, 0.0),
+            Kind::DctIII => (length, 0.0, 0.5),
+            Kind::DstI => (length + 1, 1.0, 1.0),
+            Kind::DstII => (length, 0.5, 1.0),
+            Kind::DstIII => (length, 1.0, 0.5),
+            Kind::DctIV | Kind::DstIV => (length, 0.5, 0.5),
+        }
+    }
+
+    /// Weights making the transform orthonormal, of the sample
+    /// (`output` false) or frequency index `index`.
+    fn weight(self, length: usize, index: usize, output: bool) -> f32 {
+        let (first, last) = (index == 0, index + 1 == length);
+
+        match (self, output) {
+            (Kind::DctI, _) if first || last => FRAC_1_SQRT_2,
+            (Kind::DctII, true) | (Kind::DctIII, false) if first => FRAC_1_SQRT_2,
+            (Kind::DstII, true) | (Kind::DstIII, false) if last => FRAC_1_SQRT_2,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Fast discrete cosine or sine transform of one type via the FFT.
+///
+/// The kernel e^(-i*pi*(n+alpha)*(k+beta)/d) factors into twiddles around
+/// a DFT of length 2d, the cosine and sine transforms are the sum and
+/// difference of that and its conjugate kernel. Complex input is
+/// transformed as real and imaginary part separately.
+pub struct Dtt {
+    kind: Kind,
+    fft_forward: Arc<dyn Fft<f32>>,
+    fft_inverse: Arc<dyn Fft<f32>>,
+    pre: Vec<Complex<f32>>,
+    post: Vec<Complex<f32>>,
+    forward: Vec<Complex<f32>>,
+    inverse: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Dtt {
+    pub fn new(kind: Kind, length: usize) -> Self {
+        assert!(
+            kind != Kind::DctI || length >= 2,
+            "DCT-I needs at least two samples"
+        );
+
+        let (d, alpha, beta) = kind.kernel(length);
+        let fft_forward = plan_fft_forward(2 * d);
+        let fft_inverse = plan_fft_inverse(2 * d);
+        let scratch_len = usize::max(
+            fft_forward.get_inplace_scratch_len(),
+            fft_inverse.get_inplace_scratch_len(),
+        );
+        let scale = f32::sqrt(2.0 / d as f32);
+
+        Self {
+            kind,
+            fft_forward,
+            fft_inverse,
+            pre: (0..length)
+                .map(|n| {
+                    let angle = -PI * n as f32 * beta / d as f32;
+                    Complex::new(0.0, angle).exp() * kind.weight(length, n, false)
+                })
+                .collect(),
+            post: (0..length)
+                .map(|k| {
+                    let angle = -PI * alpha * (k as f32 + beta) / d as f32;
+                    Complex::new(0.0, angle).exp() * kind.weight(length, k, true) * scale
+                })
+                .collect(),
+            forward: vec![Complex::default(); 2 * d],
+            inverse: vec![Complex::default(); 2 * d],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn process(&mut self, signal: &mut [Complex<f32>]) {
+        self.forward.fill(Complex::default());
+        self.inverse.fill(Complex::default());
+
+        for ((f, i), (x, p)) in self
+            .forward
+            .iter_mut()
+            .zip(self.inverse.iter_mut())
+            .zip(signal.iter().zip(&self.pre))
+        {
+            *f = x * p;
+            *i = x * p.conj();
+        }
+
+        self.fft_forward
+            .process_with_scratch(&mut self.forward, &mut self.scratch);
+        self.fft_inverse
+            .process_with_scratch(&mut self.inverse, &mut self.scratch);
+
+        for (k, x) in signal.iter_mut().enumerate() {
+            let plus = self.forward[k] * self.post[k];
+            let minus = self.inverse[k] * self.post[k].conj();
+
+            *x = if self.kind.is_sine() {
+                Complex::new(0.0, 0.5) * (plus - minus)
+            } else {
+                0.5 * (plus + minus)
+            };
+        }
+    }
+}
+
+/// Fractional power of one of the discrete cosine and sine transforms
+/// by eigen decomposition.
+///
+/// The transform at order a is cos(a*theta) + sin(a*theta)*J on each
+/// eigenspace of the symmetric C + C^T, where e^(+-i*theta) are the
+/// eigenvalues of the transform C on it and J, with J^2 = -1, turns by a
+/// quarter of the rotation. Order 1 is the transform itself, order -1
+/// its inverse.
+///
+/// The involutions DCT-I, DCT-IV, DST-I and DST-IV only have the
+/// eigenvalues +1 and -1. Each of them commutes with a symmetric
+/// tridiagonal matrix, a second difference plus a diagonal of cosines,
+/// like the matrix commuting with the DFT. Its eigenvectors, sorted by
+/// decreasing eigenvalue, play the role of the Hermite-Gaussians: the
+/// m-th picks up the phase e^(-i*m*a*pi) at order a, where the parity of
+/// m is given by its eigenvalue of the transform. For DCT-I this is the
+/// fractional Fourier transform of the even extension of the signal.
+///
+/// Types II and III rotate pairs of eigenvectors of C + C^T by angles in
+/// (0, pi). Eigenvectors of the eigenvalue -1 pick up e^(-i*a*pi) for
+/// type II, and type III at order a is type II at order -a, so that
+/// both stay inverse to each other at every order.
+pub struct FracDtt {
+    kind: Kind,
+    len: usize,
+    sign: f32,
+    turns: Vec<f32>,
+    vectors: Vec<f32>,
+    quarters: Vec<Complex<f32>>,
+    coefficients: Vec<Complex<f32>>,
+}
+
+impl FracDtt {
+    pub fn new(kind: Kind, length: usize) -> Self {
+        let (sign, (turns, vectors, quarters)) = match kind {
+            Kind::DctII | Kind::DstII => (1.0, rotations(kind, length)),
+            Kind::DctIII | Kind::DstIII => (-1.0, rotations(kind.inverse(), length)),
+            _ => (1.0, parities(kind, length)),
+        };
+
+        Self {
+            kind,
+            len: length,
+            sign,
+            turns,
+            vectors,
+            quarters,
+            coefficients: vec![Complex::default(); length],
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn process(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
+        for (c, vector) in self
+            .coefficients
+            .iter_mut()
+            .zip(self.vectors.chunks_exact(self.len))
+        {
+            *c = vector.iter().zip(signal.iter()).map(|(v, x)| x * v).sum();
+        }
+
+        signal.fill(Complex::default());
+        for (((c, &turns), vector), quarter) in self
+            .coefficients
+            .iter()
+            .zip(&self.turns)
+            .zip(self.vectors.chunks_exact(self.len))
+            .zip(self.quarters.chunks_exact(self.len))
+        {
+            let angle = (self.sign * fraction * turns).rem_euclid(2.0) * PI;
+            let (sin, cos) = angle.sin_cos();
+
+            for ((x, v), q) in signal.iter_mut().zip(vector).zip(quarter) {
+                *x += c * (cos * v + sin * q);
+            }
+        }
+    }
+}
+
+/// Angles in half turns, eigenvectors and their quarter turns of one of
+/// the involutions, from the commuting tridiagonal matrix.
+fn parities(kind: Kind, length: usize) -> (Vec<f32>, Vec<f32>, Vec<Complex<f32>>) {
+    let (d, alpha, _) = kind.kernel(length);
+    let boundary = |side: f64| match kind {
+        Kind::DctIV => side,
+        Kind::DstIV => -side,
+        _ => 0.0,
+    };
+    let diagonal: Vec<_> = (0..length)
+        .map(|j| {
+            let edge = if j == 0 {
+                boundary(1.0)
+            } else if j + 1 == length {
+                boundary(-1.0)
+            } else {
+                0.0
+            };
+
+            edge + 2.0 * f64::cos(std::f64::consts::PI * (j as f64 + alpha as f64) / d as f64)
+        })
+        .collect();
+    let off: Vec<_> = (1..length)
+        .map(|j| {
+            if kind == Kind::DctI && (j == 1 || j + 1 == length) {
+                std::f64::consts::SQRT_2
+            } else {
+                1.0
+            }
+        })
+        .collect();
+
+    let (values, z) = tridiagonal_eigen(&diagonal, &off);
+    let mut sorted: Vec<_> = (0..length).collect();
+    sorted.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+
+    let mut dtt = Dtt::new(kind, length);
+    let mut orders: Vec<usize> = Vec::with_capacity(length);
+    let mut vectors = Vec::with_capacity(length * length);
+
+    for k in sorted {
+        let vector: Vec<_> = (0..length).map(|j| z[j * length + k] as f32).collect();
+        let mut image: Vec<_> = vector.iter().map(|&v| Complex::new(v, 0.0)).collect();
+        dtt.process(&mut image);
+
+        let even = vector
+            .iter()
+            .zip(&image)
+            .map(|(v, t)| v * t.re)
+            .sum::<f32>()
+            > 0.0;
+        let order = match orders.last() {
+            None if even => 0,
+            None => 1,
+            Some(&m) if (m % 2 == 1) == even => m + 1,
+            Some(&m) => m + 2,
+        };
+
+        orders.push(order);
+        vectors.extend(vector);
+    }
+
+    // e^(-i*m*a*pi) = cos(m*a*pi) - i*sin(m*a*pi)
+    let quarters = vectors.iter().map(|&v| Complex::new(0.0, -v)).collect();
+
+    (
+        orders.iter().map(|&m| m as f32).collect(),
+        vectors,
+        quarters,
+    )
+}
+
+/// Angles in half turns, eigenvectors and their quarter turns of type II,
+/// from the eigen decomposition of C + C^T.
+fn rotations(kind: Kind, length: usize) -> (Vec<f32>, Vec<f32>, Vec<Complex<f32>>) {
+    let (d, alpha, beta) = kind.kernel(length);
+    let scale = f64::sqrt(2.0 / d as f64);
+    // in double precision, the eigenvalues near +-1 are sensitive to it
+    let weight = |index, output| {
+        if kind.weight(length, index, output) < 1.0 {
+            std::f64::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        }
+    };
+    let matrix: Vec<f64> = (0..length * length)
+        .map(|kn| {
+            let (k, n) = (kn / length, kn % length);
+            let angle = std::f64::consts::PI * (n as f64 + alpha as f64) * (k as f64 + beta as f64)
+                / d as f64;
+            let t = if kind.is_sine() {
+                angle.sin()
+            } else {
+                angle.cos()
+            };
+
+            t * scale * weight(n, false) * weight(k, true)
+        })
+        .collect();
+    let symmetric: Vec<f64> = (0..length * length)
+        .map(|kn| matrix[kn] + matrix[(kn % length) * length + kn / length])
+        .collect();
+
+    let (values, z) = symmetric_eigen(&symmetric, length);
+    let mut turns = Vec::with_capacity(length);
+    let mut vectors = Vec::with_capacity(length * length);
+    let mut quarters = Vec::with_capacity(length * length);
+
+    for (k, value) in values.iter().enumerate() {
+        let vector: Vec<_> = (0..length).map(|j| z[j * length + k]).collect();
+        let cos = (value / 2.0).clamp(-1.0, 1.0);
+        let sin = f64::sqrt(1.0 - cos * cos);
+
+        if sin < 1e-6 {
+            // eigenvector of C itself, the eigenvalue -1 turns like e^(-i*a*pi)
+            turns.push(if cos > 0.0 { 0.0 } else { 1.0 });
+            quarters.extend(vector.iter().map(|&v| Complex::new(0.0, -v as f32)));
+        } else {
+            // J v = (C v - cos v) / sin
+            turns.push((cos.acos() / std::f64::consts::PI) as f32);
+            quarters.extend(matrix.chunks_exact(length).zip(&vector).map(|(row, v)| {
+                let image: f64 = row.iter().zip(&vector).map(|(c, x)| c * x).sum();
+                Complex::new(((image - cos * v) / sin) as f32, 0.0)
+            }));
+        }
+
+        vectors.extend(vector.iter().map(|&v| v as f32));
+    }
+
+    (turns, vectors, quarters)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dtt::{Dtt, FracDtt, Kind};
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    fn signal(n: usize) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| Complex::new((i as f32 * 1.3).sin() + 0.2, (i as f32 * 0.4).cos()))
+            .collect()
+    }
+
+    /// Orthonormal transform by its definition.
+    fn direct(kind: Kind, x: &[Complex<f32>]) -> Vec<Complex<f32>> {
+        let n = x.len();
+        let (d, alpha, beta) = kind.kernel(n);
+
+        (0..n)
+            .map(|k| {
+                let sum: Complex<f32> = x
+                    .iter()
+                    .enumerate()
+                    .map(|(j, v)| {
+                        let angle = PI * (j as f32 + alpha) * (k as f32 + beta) / d as f32;
+                        let t = if kind.is_sine() {
+                            angle.sin()
+                        } else {
+                            angle.cos()
+                        };
+                        v * t * kind.weight(n, j, false)
+                    })
+                    .sum();
+
+                sum * kind.weight(n, k, true) * f32::sqrt(2.0 / d as f32)
+            })
+            .collect()
+    }
+
+    fn assert_close(expected: &[Complex<f32>], result: &[Complex<f32>]) {
+        for (e, r) in expected.iter().zip(result) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn matches_definition() {
+        for kind in Kind::ALL {
+            for n in [8, 9] {
+                let x = signal(n);
+                let mut result = x.clone();
+
+                Dtt::new(kind, n).process(&mut result);
+                assert_close(&direct(kind, &x), &result);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        for kind in Kind::ALL {
+            let x = signal(12);
+            let mut result = x.clone();
+
+            Dtt::new(kind, 12).process(&mut result);
+            Dtt::new(kind.inverse(), 12).process(&mut result);
+            assert_close(&x, &result);
+        }
+    }
+
+    #[test]
+    fn fractional() {
+        for kind in Kind::ALL {
+            for n in [16, 17] {
+                let x = signal(n);
+                let mut dtt = Dtt::new(kind, n);
+                let mut frac = FracDtt::new(kind, n);
+
+                let mut integer = x.clone();
+                dtt.process(&mut integer);
+                let mut one = x.clone();
+                frac.process(&mut one, 1.0);
+                assert_close(&integer, &one);
+
+                dtt.process(&mut integer);
+                let mut two = x.clone();
+                frac.process(&mut two, 2.0);
+                assert_close(&integer, &two);
+
+                let (mut once, mut twice) = (x.clone(), x.clone());
+                frac.process(&mut once, 0.7);
+                frac.process(&mut twice, 0.2);
+                frac.process(&mut twice, 0.5);
+                assert_close(&once, &twice);
+
+                frac.process(&mut once, -0.7);
+                assert_close(&x, &once);
+            }
+        }
+    }
+
+    #[test]
+    fn fractional_inverse_pairs() {
+        for kind in [Kind::DctII, Kind::DstII] {
+            let x = signal(16);
+            let mut frac = FracDtt::new(kind, 16);
+            let mut inverse = FracDtt::new(kind.inverse(), 16);
+
+            let mut minus = x.clone();
+            frac.process(&mut minus, -0.3);
+            let mut result = x.clone();
+            inverse.process(&mut result, 0.3);
+            assert_close(&minus, &result);
+
+            frac.process(&mut result, 0.3);
+            assert_close(&x, &result);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::Complex;
+
+/// Weights applied to a signal in a fractional Fourier domain.
+///
+/// Positions are measured in samples from the center sample (index N/2),
+/// which is the origin of every fractional domain.
+pub enum Mask<'w> {
+    /// Keep the samples between `from` and `to` (inclusive).
+    BandPass { from: f32, to: f32 },
+    /// Remove the samples between `from` and `to` (inclusive).
+    Notch { from: f32, to: f32 },
+    /// Multiply each sample by its own weight.
+    Weights(&'w [f32]),
+}
+
+impl Mask<'_> {
+    fn weight(&self, index: usize, position: f32) -> f32 {
+        let inside = |from: f32, to: f32| (from..=to).contains(&position);
+
+        match *self {
+            Mask::BandPass { from, to } if inside(from, to) => 1.0,
+            Mask::Notch { from, to } if !inside(from, to) => 1.0,
+            Mask::Weights(weights) => weights.get(index).copied().unwrap_or(0.0),
+            _ => 0.0,
+        }
+    }
+
+    pub fn apply(&self, signal: &mut [Complex<f32>]) {
+        let n = signal.len();
+
+        for (j, v) in signal.iter_mut().enumerate() {
+            *v *= self.weight(j, j as f32 - (n / 2) as f32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frac_filter::Mask;
+    use crate::Complex;
+    use crate::FrftImpl;
+    use crate::Signal;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn masks() {
+        let mut signal = vec![Complex::new(1.0, 1.0); 8];
+
+        Mask::BandPass {
+            from: -1.0,
+            to: 1.5,
+        }
+        .apply(&mut signal);
+        let expected = [0., 0., 0., 1., 1., 1., 0., 0.];
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e, r.re, 1e-6);
+            assert_approx_eq!(e, r.im, 1e-6);
+        }
+
+        Mask::Notch { from: 0.0, to: 0.0 }.apply(&mut signal);
+        Mask::Weights(&[0., 0., 0., 2., 3., 4.]).apply(&mut signal);
+        let expected = [0., 0., 0., 2., 0., 4., 0., 0.];
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e, r.re, 1e-6);
+        }
+    }
+
+    fn pulse_in_domain(n: usize, order: f32, center: f32) -> Vec<Complex<f32>> {
+        let mut frft = FrftImpl::new(n);
+        let mut signal: Vec<_> = (0..n)
+            .map(|i| {
+                let u = (i as f32 - (n / 2) as f32 - center) / 2.0;
+                Complex::new(f32::exp(-u * u), 0.0)
+            })
+            .collect();
+
+        frft.process_unitary(&mut signal, -order);
+        signal
+    }
+
+    fn relative_error(expected: &[Complex<f32>], result: &[Complex<f32>]) -> f32 {
+        let error: f32 = expected
+            .iter()
+            .zip(result)
+            .map(|(e, r)| (e - r).norm_sqr())
+            .sum();
+        let energy: f32 = expected.iter().map(|e| e.norm_sqr()).sum();
+
+        f32::sqrt(error / energy)
+    }
+
+    #[test]
+    fn separate_two_chirps() {
+        let n = 256;
+        // chirps of different rates, each concentrated in its own fractional
+        // domain, but centered at the same time and overlapping in time
+        let chirp_a = pulse_in_domain(n, 0.7, 40.0);
+        let chirp_b = pulse_in_domain(n, 1.3, -40.0);
+        let mut signal = Signal::new(n);
+
+        for (v, (a, b)) in signal.time.iter_mut().zip(chirp_a.iter().zip(&chirp_b)) {
+            *v = a + b;
+        }
+
+        let mixture = signal.time.clone();
+
+        signal.filter_frac_band_pass(0.7, 30.0, 50.0);
+        assert!(relative_error(&chirp_a, &signal.time) < 0.05);
+
+        signal.time.copy_from_slice(&mixture);
+        signal.filter_frac_notch(0.7, 30.0, 50.0);
+        assert!(relative_error(&chirp_b, &signal.time) < 0.05);
+
+        let weights: Vec<_> = (0..n)
+            .map(|j| {
+                let u = (j as f32 - (n / 2) as f32 + 40.0) / 10.0;
+                f32::exp(-u * u)
+            })
+            .collect();
+        signal.time.copy_from_slice(&mixture);
+        signal.filter_frac_weights(1.3, &weights);
+        assert!(relative_error(&chirp_b, &signal.time) < 0.05);
+    }
+}
@@ -4,12 +4,13 @@
 use crate::iter_into_slice;
 use crate::sinc_interp::Interpolator;
 use crate::Convolver;
-use core::iter;
 use std::f32::consts::PI;
 
 use crate::Complex;
 use crate::Fft;
-use crate::FftPlanner;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::utils::{fft_centered, flip_centered, reduced_order};
+pub use crate::utils::FrftChirps;
 
 use crate::Arc;
 
@@ -74,39 +75,54 @@ use crate::Arc;
 /// P = 2^nextpow2(N);
 /// z = ifft( fft(x,P) .* fft(y,P));
 /// z = z(1:N);
-
 pub struct Frft {
     fft_integer: Arc<dyn Fft<f32>>,
+    ifft_integer: Arc<dyn Fft<f32>>,
     interpolator: Interpolator,
     convolver: Convolver,
     conv_res: Vec<Complex<f32>>,
+    chirp_a: Vec<Complex<f32>>,
+    chirp_b: Vec<Complex<f32>>,
+    padded: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
 }
 
 impl Frft {
     pub fn new(length: usize) -> Self {
-        let mut planner = FftPlanner::new();
-        let fft_integer = planner.plan_fft_forward(length);
+        let fft_integer = plan_fft_forward(length);
+        let ifft_integer = plan_fft_inverse(length);
         let interpolator = Interpolator::new(length);
-        let (_, chirp_length_b) = Self::chirp_lengths(length);
+        let (chirp_length_a, chirp_length_b) = Self::chirp_lengths(length);
         let interp_length = Interpolator::result_len(length);
         let conv_length = chirp_length_b + interp_length + 2 * (length - 1) - 1;
         let convolver = Convolver::new(conv_length);
         let conv_res = vec![Complex::default(); conv_length];
+        let scratch_len = usize::max(
+            fft_integer.get_inplace_scratch_len(),
+            ifft_integer.get_inplace_scratch_len(),
+        );
+        let scratch = vec![Complex::default(); scratch_len];
 
         Self {
             fft_integer,
+            ifft_integer,
             interpolator,
             convolver,
             conv_res,
+            chirp_a: vec![Complex::default(); chirp_length_a],
+            chirp_b: vec![Complex::default(); chirp_length_b],
+            padded: vec![Complex::default(); chirp_length_a],
+            scratch,
         }
     }
 
     pub fn process(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
-        let _ = self.process_internal(signal, fraction);
+        let _ = self.process_internal(signal, fraction, 0, None);
     }
 
     pub fn process_scaled(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
-        let scale = f32::sqrt(self.process_internal(signal, fraction));
+        let (scale_factor, chirp_scale) = self.process_internal(signal, fraction, 0, None);
+        let scale = f32::sqrt(scale_factor * chirp_scale);
 
         for v in signal.iter_mut() {
             v.re *= scale;
@@ -114,6 +130,62 @@ impl Frft {
         }
     }
 
+    /// Like `process_scaled`, but with the normalization of frft.m,
+    /// which preserves the energy of the signal, so that orders `a`
+    /// and `-a` are inverse to each other.
+    ///
+    /// frft.m places the origin of the chirps between the two center
+    /// samples of even length signals, while the integer orders use the
+    /// sample N/2. Here the chirps are moved by half a sample to match.
+    pub fn process_unitary(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
+        self.process_unitary_internal(signal, fraction, None);
+    }
+
+    /// `process_unitary` using chirps precomputed by `unitary_chirps`.
+    pub fn process_unitary_cached(
+        &mut self,
+        signal: &mut [Complex<f32>],
+        fraction: f32,
+        chirps: &FrftChirps,
+    ) {
+        self.process_unitary_internal(signal, fraction, Some(chirps));
+    }
+
+    /// Precompute the chirps `process_unitary` uses for `fraction`.
+    pub fn unitary_chirps(&self, length: usize, fraction: f32) -> FrftChirps {
+        let order = reduced_order(fraction);
+        let (mut chirp_a, mut chirp_b) = (Vec::new(), Vec::new());
+
+        if let Some(a) = order {
+            let offset = i32::from(length.is_multiple_of(2));
+            let (a_iter, b_iter) = self.chirps(length as i32, a, offset);
+
+            chirp_a.extend(a_iter);
+            chirp_b.extend(b_iter);
+        }
+
+        FrftChirps {
+            order,
+            chirp_a,
+            chirp_b,
+        }
+    }
+
+    fn process_unitary_internal(
+        &mut self,
+        signal: &mut [Complex<f32>],
+        fraction: f32,
+        cached: Option<&FrftChirps>,
+    ) {
+        let offset = i32::from(signal.len().is_multiple_of(2));
+        let (scale_factor, chirp_scale) = self.process_internal(signal, fraction, offset, cached);
+        let scale = f32::sqrt(scale_factor) * chirp_scale;
+
+        for v in signal.iter_mut() {
+            *v *= scale;
+        }
+    }
+
     const fn chirp_lengths(n: usize) -> (usize, usize) {
         let ni = n as isize;
         let ca = (2 * ni - 1) - (-2 * ni + 2);
@@ -122,10 +194,13 @@ impl Frft {
         (ca as usize, cb as usize)
     }
 
+    /// Chirps with the origin of the pre and post multiplication
+    /// moved by `offset` half samples towards higher indices.
     fn chirps(
         &self,
         i_n: i32,
         a: f32,
+        offset: i32,
     ) -> (
         impl Iterator<Item = Complex<f32>> + Clone,
         impl Iterator<Item = Complex<f32>> + Clone,
@@ -146,6 +221,7 @@ impl Frft {
 
         // chrp_a = exp(-i*pi/N*tana2/4*(-2*N+2:2*N-2)'.^2);
         let chirp_a = ((-2 * i_n + 2)..(2 * i_n - 1)).map(move |x| {
+            let x = x - offset;
             Complex::<f32>::new(0.0, -PI / f_n * tana2 / 4.0 * ((x * x) as f32)).exp()
         });
         // chirp_b = exp(i*c*(-(4*N-4):4*N-4)'.^2)
@@ -155,66 +231,38 @@ impl Frft {
         (chirp_a, chirp_b)
     }
 
-    fn preprocess(&self, frac: &mut [Complex<f32>], fraction: f32) -> (f32, Option<f32>) {
-        let n = frac.len();
-        let f_n = n as f32;
-        let mut a = (fraction + 4.0).rem_euclid(4.0);
-
-        if a == 0.0 {
-            (1.0, None)
-        } else if a == 1.0 {
-            frac.rotate_right(n / 2);
-            self.fft_integer.process(frac);
-            frac.rotate_right(n / 2);
-
-            return (1.0 / f_n, None);
-        } else if a == 2.0 {
-            frac.reverse();
-            frac.rotate_right(1);
-
-            return (1.0, None);
-        } else if a == 3.0 {
-            frac.rotate_right(n / 2);
-            self.fft_integer.process(frac);
-            frac.rotate_right(n / 2);
-            frac.reverse();
-            frac.rotate_right(1);
-
-            return (1.0 / f_n, None);
+    /// Apply the integer part of the order, returning the normalization
+    /// of the FFT and the order left for the chirps, None if there is none.
+    fn preprocess(&mut self, frac: &mut [Complex<f32>], fraction: f32) -> (f32, Option<f32>) {
+        let f_n = frac.len() as f32;
+        let order = reduced_order(fraction);
+        let integer = (fraction - order.unwrap_or(0.0)).round().rem_euclid(4.0);
+
+        let scale_factor = if integer == 1.0 {
+            fft_centered(&self.fft_integer, frac, &mut self.scratch);
+            1.0 / f_n
+        } else if integer == 2.0 {
+            flip_centered(frac);
+            1.0
+        } else if integer == 3.0 {
+            fft_centered(&self.ifft_integer, frac, &mut self.scratch);
+            1.0 / f_n
         } else {
-            let mut scale_factor = 1.0;
-
-            if a > 2.0 {
-                frac.reverse();
-                frac.rotate_right(1);
-                a -= 2.0;
-            }
-
-            if a > 1.5 {
-                a -= 1.0;
-                frac.rotate_right(n / 2);
-                self.fft_integer.process(frac);
-                frac.rotate_right(n / 2);
+            1.0
+        };
 
-                scale_factor /= f_n;
-            }
-            if a < 0.5 {
-                a += 1.0;
-
-                frac.rotate_right(n / 2);
-                frac.reverse();
-                frac.rotate_right(1);
-                self.fft_integer.process(frac);
-                frac.rotate_right(n / 2);
-
-                scale_factor *= f_n;
-            }
-
-            return (scale_factor, Some(a));
-        }
+        (scale_factor, order)
     }
 
-    fn process_internal(&mut self, frac: &mut [Complex<f32>], fraction: f32) -> f32 {
+    /// Returns the normalization of the integer transforms
+    /// and of the chirp convolution, which are not applied to `frac`.
+    fn process_internal(
+        &mut self,
+        frac: &mut [Complex<f32>],
+        fraction: f32,
+        offset: i32,
+        cached: Option<&FrftChirps>,
+    ) -> (f32, f32) {
         let n = frac.len();
         let i_n = n as i32;
         let f_n = n as f32;
@@ -231,46 +279,59 @@ impl Frft {
             let c = PI / f_n / sina / 4.0;
             let sqrt_c_pi = f32::sqrt(c / PI);
 
-            let (chirp_a, chirp_b) = self.chirps(i_n, a);
+            match cached.filter(|chirps| chirps.order == Some(a)) {
+                Some(chirps) => {
+                    self.chirp_a.copy_from_slice(&chirps.chirp_a);
+                    self.chirp_b.copy_from_slice(&chirps.chirp_b);
+                }
+                None => {
+                    let (chirp_a, chirp_b) = self.chirps(i_n, a, offset);
+                    iter_into_slice(chirp_a, &mut self.chirp_a);
+                    iter_into_slice(chirp_b, &mut self.chirp_b);
+                }
+            }
 
             // exp(-i*(1-a)*pi/4)
             let normalizer = Complex::new(0.0, -(1.0 - a) * PI / 4.0).exp();
 
-            // [zeros(N-1,1) ; interp(f) ; zeros(N-1,1)];
-            let prepend_zeros = iter::repeat(Complex::<f32>::default()).take(n - 1);
-            let append_zeros = prepend_zeros.clone();
-            let interped_f = self.interpolator.interp(frac.iter());
-
-            let padded_f = prepend_zeros
-                .chain(interped_f.iter().cloned())
-                .chain(append_zeros);
-
+            // f = [zeros(N-1,1) ; interp(f) ; zeros(N-1,1)];
             // % chirp premultiplication
             // f = chrp_a.*f;
-            let f1 = chirp_a.clone().zip(padded_f).map(|(a, b)| a * b);
+            let interped_f = self.interpolator.interp(frac.iter());
+
+            self.padded.fill(Complex::default());
+            for ((p, f), c) in self.padded[n - 1..]
+                .iter_mut()
+                .zip(interped_f.iter())
+                .zip(self.chirp_a[n - 1..].iter())
+            {
+                *p = f * c;
+            }
 
             // % chirp convolution
             // c = pi/N/sina/4;
             // Faf = fconv(chirp_b,f);
-            self.convolver
-                .conv(chirp_b.clone(), f1.clone(), &mut self.conv_res);
-            self.conv_res.rotate_right(1);
+            self.convolver.conv(
+                self.chirp_b.iter().cloned(),
+                self.padded.iter().cloned(),
+                &mut self.conv_res,
+            );
 
             // Faf = Faf(4*N-3:8*N-7)*sqrt(c/pi);
-            let f3 = self.conv_res.iter().skip(4 * n - 4);
-
             // % chirp post multiplication
             // Faf = chrp_a.*Faf;
-            let f2 = f3.zip(chirp_a).map(|(a, b)| a * b);
-
             // % normalizing constant
             // Faf = exp(-i*(1-a)*pi/4)*Faf(N:2:end-N+1);
-            iter_into_slice(f2.skip(n - 1).step_by(2).map(|z| z * normalizer), frac);
+            let post = self.conv_res[4 * n - 4..]
+                .iter()
+                .zip(self.chirp_a.iter())
+                .map(|(f, c)| f * c * normalizer);
+            iter_into_slice(post.skip(n - 1).step_by(2), frac);
 
-            return scale_factor * sqrt_c_pi;
+            return (scale_factor, sqrt_c_pi);
         }
 
-        scale_factor
+        (scale_factor, 1.0)
     }
 }
 
@@ -283,7 +344,7 @@ mod tests {
     #[test]
     fn frft_chirp() {
         let frft = Frft::new(16);
-        let (mut c1, mut c2) = frft.chirps(16, 1.3);
+        let (mut c1, mut c2) = frft.chirps(16, 1.3, 0);
 
         assert_eq!(61, c1.clone().count());
         assert_eq!(121, c2.clone().count());
@@ -446,4 +507,60 @@ mod tests {
             assert_approx_eq!(e.im, r.im, 1e-4);
         }
     }
+
+    #[test]
+    fn frft_unitary_roundtrip() {
+        for n in [32, 33, 64] {
+            let mut frft = Frft::new(n);
+            let expected: Vec<_> = (0..n)
+                .map(|i| {
+                    let x = (i as f32 - (n / 2) as f32) / f32::sqrt(n as f32);
+                    Complex::new(f32::exp(-x * x), 0.0) * Complex::new(0.0, 0.7 * x).exp()
+                })
+                .collect();
+            let energy: f32 = expected.iter().map(|v| v.norm_sqr()).sum();
+
+            for fraction in [0.3, 0.5, 1.0, 1.25, 1.7, 2.6, 3.5] {
+                let mut signal = expected.clone();
+
+                frft.process_unitary(&mut signal, fraction);
+                let transformed: f32 = signal.iter().map(|v| v.norm_sqr()).sum();
+                assert_approx_eq!(energy, transformed, 1e-3 * energy);
+
+                frft.process_unitary(&mut signal, -fraction);
+                for (e, r) in expected.iter().zip(signal.iter()) {
+                    assert_approx_eq!(e.re, r.re, 1e-3);
+                    assert_approx_eq!(e.im, r.im, 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn frft_integer_roundtrip() {
+        for n in [5, 8, 9, 16] {
+            let mut frft = Frft::new(n);
+            let expected: Vec<_> = (0..n)
+                .map(|i| Complex::new((i as f32 * 0.9).sin(), (i as f32 * 0.4).cos()))
+                .collect();
+
+            for (forward, backward) in [(1.0, -1.0), (1.0, 3.0), (2.0, 2.0), (1.0, 1.0)] {
+                let mut signal = expected.clone();
+
+                frft.process_scaled(&mut signal, forward);
+                frft.process_scaled(&mut signal, backward);
+
+                if forward + backward == 2.0 {
+                    // applying the fourier transform twice mirrors the signal
+                    signal.reverse();
+                    signal.rotate_right(1 - n % 2);
+                }
+
+                for (e, r) in expected.iter().zip(signal.iter()) {
+                    assert_approx_eq!(e.re, r.re, 1e-4);
+                    assert_approx_eq!(e.im, r.im, 1e-4);
+                }
+            }
+        }
+    }
 }
@@ -7,7 +7,9 @@ use crate::Arc;
 use crate::Complex;
 use crate::Convolver;
 use crate::Fft;
-use crate::FftPlanner;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::utils::{fft_centered, flip_centered, reduced_order};
+pub use crate::utils::FrftChirps;
 use std::f32::consts::PI;
 
 /// Implementation based on the matlab code
@@ -73,15 +75,16 @@ use std::f32::consts::PI;
 ///   z = ifft(z);
 ///   z = z(N:-1:1);
 /// end
-
 pub struct Frft2 {
     fft_integer: Arc<dyn Fft<f32>>,
+    ifft_integer: Arc<dyn Fft<f32>>,
     convolver: Convolver,
 
     f1: Vec<Complex<f32>>,
     f0c: Vec<Complex<f32>>,
     f1c: Vec<Complex<f32>>,
     h0: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
 }
 
 impl Frft2 {
@@ -89,11 +92,18 @@ impl Frft2 {
         let sinc_len = 2 * length - 1;
         let fft_conv_len = conv_length(length, sinc_len);
 
-        let mut planner = FftPlanner::new();
-        let fft_integer = planner.plan_fft_forward(length);
+        let fft_integer = plan_fft_forward(length);
+        let ifft_integer = plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_integer.get_inplace_scratch_len(),
+            ifft_integer.get_inplace_scratch_len(),
+        );
+        let scratch = vec![Complex::default(); scratch_len];
 
         Self {
             fft_integer,
+            ifft_integer,
+            scratch,
             convolver: Convolver::new(fft_conv_len),
             f1: vec![Complex::default(); fft_conv_len],
             f0c: vec![Complex::default(); fft_conv_len],
@@ -103,11 +113,11 @@ impl Frft2 {
     }
 
     pub fn process(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
-        let _ = self.process_internal(signal, fraction);
+        let _ = self.process_internal(signal, fraction, 0.0, None);
     }
 
     pub fn process_scaled(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
-        let scale = f32::sqrt(self.process_internal(signal, fraction));
+        let scale = f32::sqrt(self.process_internal(signal, fraction, 0.0, None));
 
         for v in signal.iter_mut() {
             v.re *= scale;
@@ -115,26 +125,89 @@ impl Frft2 {
         }
     }
 
+    /// Like `process_scaled`, but with the origin of the chirps moved
+    /// to the sample N/2 for even lengths, like the integer orders,
+    /// so that orders `a` and `-a` are inverse to each other.
+    pub fn process_unitary(&mut self, signal: &mut [Complex<f32>], fraction: f32) {
+        self.process_unitary_internal(signal, fraction, None);
+    }
+
+    /// `process_unitary` using chirps precomputed by `unitary_chirps`.
+    pub fn process_unitary_cached(
+        &mut self,
+        signal: &mut [Complex<f32>],
+        fraction: f32,
+        chirps: &FrftChirps,
+    ) {
+        self.process_unitary_internal(signal, fraction, Some(chirps));
+    }
+
+    /// Precompute the chirps `process_unitary` uses for `fraction`.
+    pub fn unitary_chirps(&self, length: usize, fraction: f32) -> FrftChirps {
+        let order = reduced_order(fraction);
+        let (mut chirp_a, mut chirp_b) = (Vec::new(), Vec::new());
+
+        if let Some(a) = order {
+            let (a_iter, b_iter) = self.chirps(length, a, Self::unitary_offset(length));
+
+            chirp_a.extend(a_iter);
+            chirp_b.extend(b_iter);
+        }
+
+        FrftChirps {
+            order,
+            chirp_a,
+            chirp_b,
+        }
+    }
+
+    fn unitary_offset(length: usize) -> f32 {
+        if length.is_multiple_of(2) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn process_unitary_internal(
+        &mut self,
+        signal: &mut [Complex<f32>],
+        fraction: f32,
+        cached: Option<&FrftChirps>,
+    ) {
+        let offset = Self::unitary_offset(signal.len());
+        let scale = f32::sqrt(self.process_internal(signal, fraction, offset, cached));
+
+        for v in signal.iter_mut() {
+            *v *= scale;
+        }
+    }
+
+    /// Chirps with the origin of the pre and post multiplication
+    /// moved by `offset` half samples towards higher indices.
     fn chirps(
         &self,
         n: usize,
         a: f32,
+        offset: f32,
     ) -> (
         impl Iterator<Item = Complex<f32>> + Clone,
         impl Iterator<Item = Complex<f32>> + Clone,
     ) {
         let f_n = n as f32;
         let alpha = a * PI / 2.0;
-        let s = PI / (f_n + 1.0) / alpha.sin() / 4.0;
-        let t = PI / (f_n + 1.0) * (alpha / 2.0).tan() / 4.0;
+        // frft2.m uses N+1 here, N matches the sample spacing
+        // of the integer orders used to reduce the order
+        let s = PI / f_n / alpha.sin() / 4.0;
+        let t = PI / f_n * (alpha / 2.0).tan() / 4.0;
         // chrp = exp(-i*t*(-N+1:N-1)'.^2);
         let chirp_a = (0..(2 * n - 1))
-            .map(move |i| -f_n + 1.0 + i as f32)
-            .map(move |x| Complex::<f32>::new(0.0, -1.0 * t * x * x).exp());
+            .map(move |i| -f_n + 1.0 + i as f32 - offset)
+            .map(move |x| Complex::<f32>::new(0.0, -t * x * x).exp());
         // chrp = exp(i*s*[-(2*N-1):(2*N-1)]'.^2);
         let chirp_b = (0..(4 * n - 1))
             .map(move |i| -(2.0 * f_n - 1.0) + i as f32)
-            .map(move |x| Complex::<f32>::new(0.0, 1.0 * s * x * x).exp());
+            .map(move |x| Complex::<f32>::new(0.0, s * x * x).exp());
 
         (chirp_a, chirp_b)
     }
@@ -144,120 +217,137 @@ impl Frft2 {
 
         (0..(2 * n - 1))
             .map(move |i| -(2.0 * f_n - 3.0) + 2.0 * i as f32)
-            .map(|x| sinc(x) * 0.5)
+            .map(|x| sinc(x / 2.0))
     }
 
-    fn preprocess(&self, frac: &mut [Complex<f32>], fraction: f32) -> (f32, Option<f32>) {
-        let n = frac.len();
-        let f_n = n as f32;
-        let mut a = (fraction + 4.0).rem_euclid(4.0);
-
-        if a == 0.0 {
-            (1.0, None)
-        } else if a == 1.0 {
-            frac.rotate_right(n / 2);
-            self.fft_integer.process(frac);
-            frac.rotate_right(n / 2);
-
-            return (1.0 / f_n, None);
-        } else if a == 2.0 {
-            frac.reverse();
-            frac.rotate_right(1);
-
-            return (1.0, None);
-        } else if a == 3.0 {
-            frac.rotate_right(n / 2);
-            self.fft_integer.process(frac);
-            frac.rotate_right(n / 2);
-            frac.reverse();
-            frac.rotate_right(1);
-
-            return (1.0 / f_n, None);
+    /// Apply the integer part of the order, returning the normalization
+    /// of the FFT and the order left for the chirps, None if there is none.
+    fn preprocess(&mut self, frac: &mut [Complex<f32>], fraction: f32) -> (f32, Option<f32>) {
+        let f_n = frac.len() as f32;
+        let order = reduced_order(fraction);
+        let integer = (fraction - order.unwrap_or(0.0)).round().rem_euclid(4.0);
+
+        let scale_factor = if integer == 1.0 {
+            fft_centered(&self.fft_integer, frac, &mut self.scratch);
+            1.0 / f_n
+        } else if integer == 2.0 {
+            flip_centered(frac);
+            1.0
+        } else if integer == 3.0 {
+            fft_centered(&self.ifft_integer, frac, &mut self.scratch);
+            1.0 / f_n
         } else {
-            let mut scale_factor = 1.0;
-
-            if a > 2.0 {
-                frac.reverse();
-                frac.rotate_right(1);
-                a -= 2.0;
-            }
-
-            if a > 1.5 {
-                a -= 1.0;
-                frac.rotate_right(n / 2);
-                self.fft_integer.process(frac);
-                frac.rotate_right(n / 2);
-
-                scale_factor /= f_n;
-            }
-            if a < 0.5 {
-                a += 1.0;
+            1.0
+        };
 
-                frac.rotate_right(n / 2);
-                frac.reverse();
-                frac.rotate_right(1);
-                self.fft_integer.process(frac);
-                frac.rotate_right(n / 2);
-
-                scale_factor *= f_n;
-            }
-
-            return (scale_factor, Some(a));
-        }
+        (scale_factor, order)
     }
 
-    fn process_internal(&mut self, frac: &mut [Complex<f32>], fraction: f32) -> f32 {
+    fn process_internal(
+        &mut self,
+        frac: &mut [Complex<f32>],
+        fraction: f32,
+        offset: f32,
+        cached: Option<&FrftChirps>,
+    ) -> f32 {
         let n = frac.len();
-        let f_n = n as f32;
-
         let (scale_factor, adjusted_a) = self.preprocess(frac, fraction);
 
         if let Some(a) = adjusted_a {
-            let alpha = a * PI / 2.0;
-            let s = PI / (f_n + 1.0) / alpha.sin() / 4.0;
-            let cs = Complex::<f32>::new(0.0, -1.0 * (1.0 - a) * PI / 4.0).exp() / (s / PI).sqrt();
-
-            let (chirp_a, chirp_b) = self.chirps(n, a);
-
-            let sinc_iter = self.sinc(n);
-
-            self.convolver
-                .conv(frac.iter().cloned(), sinc_iter, &mut self.f1);
-            let f1_slice = self.f1[n..(2 * n - 1)].iter().rev();
-
-            let l0 = chirp_a.clone().step_by(2);
-            let l1 = chirp_a.skip(1).step_by(2);
-            let e0 = chirp_b.clone().step_by(2);
-            let e1 = chirp_b.skip(1).step_by(2);
-
-            let f0m_iter = frac.iter().zip(l0.clone()).map(|(a, b)| a * b);
-            let f1m_iter = f1_slice.zip(l1).map(|(a, b)| a * b);
-
-            self.convolver.conv_spectral(f0m_iter, e0, &mut self.f0c);
-            self.convolver.conv_spectral(f1m_iter, e1, &mut self.f1c);
-
-            iter_into_slice(
-                self.f0c.iter().zip(self.f1c.iter()).map(|(a, b)| a + b),
-                &mut self.h0,
-            );
-            self.convolver.fft(&mut self.h0);
-            self.h0.reverse();
-            self.h0.rotate_right(1);
-
-            let result = l0
-                .enumerate()
-                .map(|(i, l)| cs * l * self.h0[n + i] * f32::sqrt(f_n));
-            iter_into_slice(result, frac);
+            match cached.filter(|chirps| chirps.order == Some(a)) {
+                Some(chirps) => self.process_chirps(
+                    frac,
+                    a,
+                    chirps.chirp_a.iter().copied(),
+                    chirps.chirp_b.iter().copied(),
+                ),
+                None => {
+                    let (chirp_a, chirp_b) = self.chirps(n, a, offset);
+                    self.process_chirps(frac, a, chirp_a, chirp_b);
+                }
+            }
         }
 
         scale_factor
     }
+
+    /// The general case for 0.5 < a < 1.5.
+    fn process_chirps(
+        &mut self,
+        frac: &mut [Complex<f32>],
+        a: f32,
+        chirp_a: impl Iterator<Item = Complex<f32>> + Clone,
+        chirp_b: impl Iterator<Item = Complex<f32>> + Clone,
+    ) {
+        let n = frac.len();
+        let f_n = n as f32;
+
+        let alpha = a * PI / 2.0;
+        let s = PI / f_n / alpha.sin() / 4.0;
+        let cs = Complex::<f32>::new(0.0, -(1.0 - a) * PI / 4.0).exp() * (s / PI).sqrt();
+        let conv_len = self.h0.len() as f32;
+
+        let sinc_iter = self.sinc(n);
+
+        self.convolver
+            .conv(frac.iter().cloned(), sinc_iter, &mut self.f1);
+        let f1_slice = self.f1[n - 1..(2 * n - 2)].iter();
+
+        let l0 = chirp_a.clone().step_by(2);
+        let l1 = chirp_a.skip(1).step_by(2);
+        let e1 = chirp_b.clone().step_by(2);
+        let e0 = chirp_b.skip(1).step_by(2);
+
+        let f0m_iter = frac.iter().zip(l0.clone()).map(|(a, b)| a * b);
+        let f1m_iter = f1_slice.zip(l1).map(|(a, b)| a * b);
+
+        self.convolver.conv_spectral(f0m_iter, e0, &mut self.f0c);
+        self.convolver.conv_spectral(f1m_iter, e1, &mut self.f1c);
+
+        iter_into_slice(
+            self.f0c.iter().zip(self.f1c.iter()).map(|(a, b)| a + b),
+            &mut self.h0,
+        );
+        // h0 = ifft(f0+f1);
+        self.convolver.ifft(&mut self.h0);
+
+        let result = l0
+            .enumerate()
+            .map(|(i, l)| cs * l * self.h0[n - 1 + i] / conv_len);
+        iter_into_slice(result, frac);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::Complex;
     use crate::frft2::Frft2;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn frft2_unitary_roundtrip() {
+        for n in [32, 33, 64] {
+            let mut frft = Frft2::new(n);
+            let expected: Vec<_> = (0..n)
+                .map(|i| {
+                    let x = (i as f32 - (n / 2) as f32) / f32::sqrt(n as f32);
+                    Complex::new(f32::exp(-x * x), 0.0) * Complex::new(0.0, 0.7 * x).exp()
+                })
+                .collect();
+
+            for fraction in [0.3, 0.5, 1.0, 1.25, 1.7, 2.6, 3.5] {
+                let mut signal = expected.clone();
+
+                frft.process_unitary(&mut signal, fraction);
+                frft.process_unitary(&mut signal, -fraction);
+
+                for (e, r) in expected.iter().zip(signal.iter()) {
+                    assert_approx_eq!(e.re, r.re, 1e-3);
+                    assert_approx_eq!(e.im, r.im, 1e-3);
+                }
+            }
+        }
+    }
 
     #[test]
     fn frft2_0() {
@@ -303,10 +393,11 @@ mod tests {
 
     #[test]
     fn frft2_2() {
+        // order 2 mirrors at the center sample N/2, the sample at -1 moves to +1
         let mut frft = Frft2::new(4);
         let mut signal = [
-            Complex::new(1.0, 0.0),
             Complex::new(0.0, 0.0),
+            Complex::new(1.0, 0.0),
             Complex::new(0.0, 0.0),
             Complex::new(0.0, 0.0),
         ];
@@ -0,0 +1,294 @@
+use crate::linalg::tridiagonal_eigen;
+use crate::Complex;
+use std::f64::consts::PI;
+
+/// The lowest order discrete Hermite-Gaussians of a signal length.
+///
+/// They are the eigenvectors of a matrix commuting with the centered DFT,
+/// sorted by order, and converge to samples of the continuous
+/// Hermite-Gaussians for growing length. The mode of order k is an
+/// eigenvector of the DFT with eigenvalue e^(-i*k*pi/2), and so picks up
+/// the phase e^(-i*k*a*pi/2) under the fractional Fourier transform of
+/// order a. Order 0 is the discrete counterpart of the gaussian.
+pub struct HermiteBasis {
+    len: usize,
+    orders: Vec<usize>,
+    /// Row k holds the mode of order `orders[k]`, centered at index N/2.
+    vectors: Vec<f32>,
+}
+
+impl HermiteBasis {
+    /// The first `count` modes of length `length`, at most `length`.
+    pub fn new(length: usize, count: usize) -> Self {
+        let modes = hermite_vectors(length, count);
+
+        Self {
+            len: length,
+            orders: modes.iter().map(|(k, _)| *k).collect(),
+            vectors: modes
+                .iter()
+                .flat_map(|(_, v)| v.iter().map(|&x| x as f32))
+                .collect(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Order of the `k`-th mode, which is k except for the last mode
+    /// of even lengths.
+    pub fn order(&self, k: usize) -> usize {
+        self.orders[k]
+    }
+
+    pub fn mode(&self, k: usize) -> &[f32] {
+        &self.vectors[k * self.len..(k + 1) * self.len]
+    }
+
+    /// Project `signal` onto each mode. As the modes are orthonormal, the
+    /// coefficients of all modes represent the signal exactly.
+    pub fn decompose(&self, signal: &[Complex<f32>], coefficients: &mut [Complex<f32>]) {
+        for (c, mode) in coefficients
+            .iter_mut()
+            .zip(self.vectors.chunks_exact(self.len))
+        {
+            *c = mode.iter().zip(signal).map(|(m, x)| x * m).sum();
+        }
+    }
+
+    /// Sum of the modes weighted by `coefficients`.
+    pub fn synthesize(&self, coefficients: &[Complex<f32>], signal: &mut [Complex<f32>]) {
+        signal.fill(Complex::default());
+
+        for (c, mode) in coefficients.iter().zip(self.vectors.chunks_exact(self.len)) {
+            for (x, m) in signal.iter_mut().zip(mode) {
+                *x += c * m;
+            }
+        }
+    }
+}
+
+/// Entry (p, q) of the matrix commuting with the DFT,
+/// 2cos(2*pi*p/N) - 4 on the diagonal and 1 on the cyclic off diagonals.
+fn commuting(n: usize, p: usize, q: usize) -> f64 {
+    let diagonal = if p == q {
+        2.0 * f64::cos(2.0 * PI * p as f64 / n as f64) - 4.0
+    } else {
+        0.0
+    };
+    let next = ((p + 1) % n == q) as usize + ((q + 1) % n == p) as usize;
+
+    diagonal + next as f64
+}
+
+/// Even (`sign` 1) or odd (`sign` -1) basis vector j, as its nonzero
+/// entries: index and value.
+fn symmetric_basis(n: usize, j: usize, sign: f64) -> Vec<(usize, f64)> {
+    if j == 0 || 2 * j == n {
+        vec![(j, 1.0)]
+    } else {
+        vec![(j, f64::sqrt(0.5)), (n - j, sign * f64::sqrt(0.5))]
+    }
+}
+
+/// Eigenvectors of the commuting matrix restricted to the even or odd
+/// vectors, where it is tridiagonal, sorted by decreasing eigenvalue.
+fn symmetric_eigenvectors(n: usize, basis: &[Vec<(usize, f64)>]) -> Vec<Vec<f64>> {
+    let m = basis.len();
+    let entry = |a: &[(usize, f64)], b: &[(usize, f64)]| -> f64 {
+        a.iter()
+            .flat_map(|&(p, x)| b.iter().map(move |&(q, y)| x * commuting(n, p, q) * y))
+            .sum()
+    };
+    let diagonal: Vec<_> = basis.iter().map(|b| entry(b, b)).collect();
+    let off: Vec<_> = basis.windows(2).map(|w| entry(&w[0], &w[1])).collect();
+
+    let (values, z) = tridiagonal_eigen(&diagonal, &off);
+    let mut order: Vec<_> = (0..m).collect();
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+
+    order
+        .iter()
+        .map(|&k| {
+            let mut vector = vec![0.0; n];
+
+            for (i, b) in basis.iter().enumerate() {
+                for &(p, x) in b {
+                    vector[p] += x * z[i * m + k];
+                }
+            }
+
+            // origin at the center sample
+            vector.rotate_right(n / 2);
+            vector
+        })
+        .collect()
+}
+
+/// Samples of the first `count` continuous Hermite-Gaussians on the grid
+/// of the centered DFT, x = (j - N/2) * sqrt(2*pi/N), normalized to unit norm.
+fn sampled_hermite_gaussians(n: usize, count: usize) -> Vec<Vec<f64>> {
+    let spacing = f64::sqrt(2.0 * PI / n as f64);
+    let x: Vec<_> = (0..n)
+        .map(|j| (j as f64 - (n / 2) as f64) * spacing)
+        .collect();
+    let mut modes: Vec<Vec<f64>> = Vec::with_capacity(count);
+
+    for k in 0..count {
+        let mode: Vec<_> = match k {
+            0 => x.iter().map(|x| f64::exp(-x * x / 2.0)).collect(),
+            1 => x
+                .iter()
+                .zip(&modes[0])
+                .map(|(x, p)| f64::sqrt(2.0) * x * p)
+                .collect(),
+            _ => x
+                .iter()
+                .zip(modes[k - 1].iter().zip(&modes[k - 2]))
+                .map(|(x, (p, q))| {
+                    f64::sqrt(2.0 / k as f64) * x * p - f64::sqrt((k - 1) as f64 / k as f64) * q
+                })
+                .collect(),
+        };
+
+        modes.push(mode);
+    }
+
+    for mode in modes.iter_mut() {
+        let norm = mode.iter().map(|v| v * v).sum::<f64>().sqrt();
+        mode.iter_mut().for_each(|v| *v /= norm);
+    }
+
+    modes
+}
+
+/// The first `count` discrete Hermite-Gaussian vectors of length `n` with
+/// their orders, sorted by order.
+///
+/// Even modes have the orders 0, 2, 4, ..., odd modes 1, 3, 5, ... .
+/// For even `n` the highest even mode has order n and there is no mode
+/// of order n-1. The signs follow the continuous Hermite-Gaussians.
+fn hermite_vectors(n: usize, count: usize) -> Vec<(usize, Vec<f64>)> {
+    let even: Vec<_> = (0..=n / 2).map(|j| symmetric_basis(n, j, 1.0)).collect();
+    let odd: Vec<_> = (1..n.div_ceil(2))
+        .map(|j| symmetric_basis(n, j, -1.0))
+        .collect();
+
+    let mut modes: Vec<_> = symmetric_eigenvectors(n, &even)
+        .into_iter()
+        .enumerate()
+        .map(|(r, v)| (2 * r, v))
+        .chain(
+            symmetric_eigenvectors(n, &odd)
+                .into_iter()
+                .enumerate()
+                .map(|(r, v)| (2 * r + 1, v)),
+        )
+        .collect();
+
+    modes.sort_by_key(|(k, _)| *k);
+    modes.truncate(count);
+
+    let reference = sampled_hermite_gaussians(n, modes.last().map_or(0, |(k, _)| k + 1));
+    for (k, mode) in modes.iter_mut() {
+        let dot: f64 = mode.iter().zip(&reference[*k]).map(|(a, b)| a * b).sum();
+
+        if dot < 0.0 {
+            mode.iter_mut().for_each(|v| *v = -*v);
+        }
+    }
+
+    modes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hermite::{sampled_hermite_gaussians, HermiteBasis};
+    use crate::Complex;
+    use crate::Signal;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn orthonormal_modes() {
+        for n in [16, 17] {
+            let basis = HermiteBasis::new(n, n);
+            let orders: Vec<_> = (0..n).map(|k| basis.order(k)).collect();
+            let expected: Vec<_> = (0..n)
+                .map(|k| if n % 2 == 0 && k == n - 1 { n } else { k })
+                .collect();
+            assert_eq!(expected, orders);
+
+            for i in 0..n {
+                for j in 0..n {
+                    let dot: f32 = basis
+                        .mode(i)
+                        .iter()
+                        .zip(basis.mode(j))
+                        .map(|(a, b)| a * b)
+                        .sum();
+                    assert_approx_eq!(if i == j { 1.0 } else { 0.0 }, dot, 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn low_orders_approach_continuous() {
+        let n = 128;
+        let basis = HermiteBasis::new(n, 4);
+        let reference = sampled_hermite_gaussians(n, 4);
+
+        assert_eq!(4, basis.count());
+        for (k, expected) in reference.iter().enumerate() {
+            for (e, r) in expected.iter().zip(basis.mode(k)) {
+                assert_approx_eq!(*e as f32, r, 5e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn decompose_and_synthesize() {
+        let n = 33;
+        let basis = HermiteBasis::new(n, n);
+        let signal: Vec<_> = (0..n)
+            .map(|i| Complex::new((i as f32 * 0.7).sin(), (i as f32 * 0.3).cos()))
+            .collect();
+        let mut coefficients = vec![Complex::default(); n];
+        let mut result = vec![Complex::default(); n];
+
+        basis.decompose(&signal, &mut coefficients);
+        basis.synthesize(&coefficients, &mut result);
+
+        for (e, r) in signal.iter().zip(&result) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn modes_pick_up_phase_under_frac() {
+        let n = 129;
+        let fraction = 0.6;
+        let mut signal = Signal::new(n);
+
+        for k in 0..4 {
+            let mut coefficients = vec![0.0; 2 * (k + 1)];
+            coefficients[2 * k] = 1.0;
+
+            signal.synthesize_hermite(&coefficients);
+            signal.update_frac(fraction);
+            signal.time.copy_from_slice(&signal.frac);
+            signal.update_hermite(k + 1);
+
+            let c = signal.hermite[k];
+            let expected = Complex::new(0.0, -(k as f32) * fraction * PI / 2.0).exp();
+            assert_approx_eq!(expected.re, c.re / c.norm(), 1e-2);
+            assert_approx_eq!(expected.im, c.im / c.norm(), 1e-2);
+            // all energy stays in the mode
+            let energy: f32 = signal.time.iter().map(|v| v.norm_sqr()).sum();
+            assert_approx_eq!(energy, c.norm_sqr(), 1e-2 * energy);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::utils::fft_centered;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use std::f32::consts::PI;
+
+/// Analytic signal of a real valued signal via the FFT.
+///
+/// The negative frequencies of the spectrum are removed and the positive
+/// ones doubled, so the real part of the analytic signal is the original
+/// signal and its imaginary part is the Hilbert transform.
+pub struct Hilbert {
+    fft_forward: Arc<dyn Fft<f32>>,
+    fft_inverse: Arc<dyn Fft<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Hilbert {
+    pub fn new(length: usize) -> Self {
+        let fft_forward = plan_fft_forward(length);
+        let fft_inverse = plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_forward.get_inplace_scratch_len(),
+            fft_inverse.get_inplace_scratch_len(),
+        );
+
+        Self {
+            fft_forward,
+            fft_inverse,
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    /// Compute the analytic signal of the real part of `signal` into `output`.
+    ///
+    /// `spectrum` receives the centered, one-sided spectrum the analytic
+    /// signal is synthesized from. The DC bin, and the nyquist bin of even
+    /// lengths, are kept as they are.
+    pub fn analytic(
+        &mut self,
+        signal: &[Complex<f32>],
+        spectrum: &mut [Complex<f32>],
+        output: &mut [Complex<f32>],
+    ) {
+        let n = signal.len();
+
+        for (s, x) in spectrum.iter_mut().zip(signal) {
+            *s = Complex::new(x.re, 0.0);
+        }
+
+        fft_centered(&self.fft_forward, spectrum, &mut self.scratch);
+
+        for (j, bin) in spectrum.iter_mut().enumerate() {
+            let b = j as isize - (n / 2) as isize;
+
+            if b > 0 {
+                *bin *= 2.0;
+            } else if b < 0 && !(n.is_multiple_of(2) && j == 0) {
+                *bin = Complex::default();
+            }
+        }
+
+        output.copy_from_slice(spectrum);
+        fft_centered(&self.fft_inverse, output, &mut self.scratch);
+
+        for v in output.iter_mut() {
+            *v /= n as f32;
+        }
+    }
+}
+
+/// Multiply the positive half of a centered signal by `e^(-i*angle)`
+/// and the negative half by `e^(i*angle)`.
+///
+/// Applied to a spectrum this is the fractional Hilbert transform,
+/// `cos(angle)` times the signal plus `sin(angle)` times its Hilbert
+/// transform. The origin, and the nyquist bin of even lengths, which
+/// belong to both halves, are weighted with `cos(angle)`.
+pub fn rotate_half_planes(signal: &mut [Complex<f32>], angle: f32) {
+    let n = signal.len();
+    let positive = Complex::new(0.0, -angle).exp();
+
+    for (j, v) in signal.iter_mut().enumerate() {
+        let u = j as isize - (n / 2) as isize;
+
+        if u == 0 || (n.is_multiple_of(2) && j == 0) {
+            *v *= angle.cos();
+        } else if u > 0 {
+            *v *= positive;
+        } else {
+            *v *= positive.conj();
+        }
+    }
+}
+
+/// Instantaneous amplitude of an analytic signal.
+pub fn envelope(analytic: &[Complex<f32>], output: &mut [f32]) {
+    for (out, z) in output.iter_mut().zip(analytic) {
+        *out = z.norm();
+    }
+}
+
+/// Instantaneous phase of an analytic signal,
+/// unwrapped to be continuous across the +-pi borders.
+pub fn unwrapped_phase(analytic: &[Complex<f32>], output: &mut [f32]) {
+    let mut previous: Option<(f32, f32)> = None;
+
+    for (out, z) in output.iter_mut().zip(analytic) {
+        let arg = z.arg();
+
+        *out = match previous {
+            Some((previous_arg, previous_out)) => {
+                let delta = arg - previous_arg;
+                previous_out + delta - 2.0 * PI * (delta / (2.0 * PI)).round()
+            }
+            None => arg,
+        };
+
+        previous = Some((arg, *out));
+    }
+}
+
+/// Instantaneous frequency in bins, i.e. cycles per signal length,
+/// as the central difference of the unwrapped `phase`.
+pub fn instantaneous_frequency(phase: &[f32], output: &mut [f32]) {
+    let n = phase.len();
+    let scale = n as f32 / (2.0 * PI);
+
+    if n < 2 {
+        output.fill(0.0);
+        return;
+    }
+
+    for (i, out) in output.iter_mut().enumerate() {
+        let (from, to) = (i.saturating_sub(1), usize::min(i + 1, n - 1));
+
+        *out = scale * (phase[to] - phase[from]) / (to - from) as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hilbert::{envelope, instantaneous_frequency, unwrapped_phase, Hilbert};
+    use crate::Complex;
+    use crate::FrftImpl;
+    use crate::Signal;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn analytic_of_cosine() {
+        for n in [32, 33] {
+            let mut hilbert = Hilbert::new(n);
+            let omega = 2.0 * PI * 5.0 / n as f32;
+            let signal: Vec<_> = (0..n)
+                .map(|i| Complex::new((omega * i as f32).cos(), 0.0))
+                .collect();
+            let mut spectrum = vec![Complex::default(); n];
+            let mut analytic = vec![Complex::default(); n];
+
+            hilbert.analytic(&signal, &mut spectrum, &mut analytic);
+
+            for (i, z) in analytic.iter().enumerate() {
+                let e = Complex::new(0.0, omega * i as f32).exp();
+                assert_approx_eq!(e.re, z.re, 1e-4);
+                assert_approx_eq!(e.im, z.im, 1e-4);
+            }
+            for bin in &spectrum[..n / 2] {
+                assert_approx_eq!(0.0, bin.norm(), 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn amplitude_modulation() {
+        let n = 256;
+        let mut hilbert = Hilbert::new(n);
+        let modulation = |i: usize| 1.0 + 0.5 * (2.0 * PI * 2.0 * i as f32 / n as f32).cos();
+        let signal: Vec<_> = (0..n)
+            .map(|i| {
+                let carrier = (2.0 * PI * 40.0 * i as f32 / n as f32).cos();
+                Complex::new(modulation(i) * carrier, 0.0)
+            })
+            .collect();
+        let mut spectrum = vec![Complex::default(); n];
+        let mut analytic = vec![Complex::default(); n];
+        let mut amplitude = vec![0.0; n];
+        let mut phase = vec![0.0; n];
+        let mut freq = vec![0.0; n];
+
+        hilbert.analytic(&signal, &mut spectrum, &mut analytic);
+        envelope(&analytic, &mut amplitude);
+        unwrapped_phase(&analytic, &mut phase);
+        instantaneous_frequency(&phase, &mut freq);
+
+        for (i, a) in amplitude.iter().enumerate() {
+            assert_approx_eq!(modulation(i), a, 1e-4);
+        }
+        for f in &freq {
+            assert_approx_eq!(40.0, f, 1e-2);
+        }
+        assert_approx_eq!(
+            2.0 * PI * 40.0 * (n - 1) as f32 / n as f32,
+            phase[n - 1],
+            1e-2
+        );
+    }
+
+    #[test]
+    fn chirp_frequency_is_linear() {
+        let n = 512;
+        let mut hilbert = Hilbert::new(n);
+        // phase 2*pi*(f0*t + rate*t^2/2) with t in samples
+        let (f0, rate) = (0.05, 0.3 / n as f32);
+        let signal: Vec<_> = (0..n)
+            .map(|i| {
+                let t = i as f32;
+                Complex::new((2.0 * PI * (f0 * t + 0.5 * rate * t * t)).cos(), 0.0)
+            })
+            .collect();
+        let mut spectrum = vec![Complex::default(); n];
+        let mut analytic = vec![Complex::default(); n];
+        let mut phase = vec![0.0; n];
+        let mut freq = vec![0.0; n];
+
+        hilbert.analytic(&signal, &mut spectrum, &mut analytic);
+        unwrapped_phase(&analytic, &mut phase);
+        instantaneous_frequency(&phase, &mut freq);
+
+        // away from the borders, where the periodic extension jumps
+        for (i, f) in freq.iter().enumerate().take(n - 128).skip(64) {
+            let e = (f0 + rate * i as f32) * n as f32;
+            assert_approx_eq!(e, f, 0.5);
+        }
+    }
+
+    #[test]
+    fn fractional_hilbert_of_cosine() {
+        for n in [32, 33] {
+            let mut signal = Signal::new(n);
+            let omega = 2.0 * PI * 3.0 / n as f32;
+            let angle = 0.4;
+
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                *v = Complex::new((omega * i as f32).cos(), 0.0);
+            }
+
+            signal.hilbert_time(angle, 1.0);
+
+            for (i, v) in signal.time.iter().enumerate() {
+                let phase = omega * i as f32;
+                let e = angle.cos() * phase.cos() + angle.sin() * phase.sin();
+                assert_approx_eq!(e, v.re, 1e-4);
+                assert_approx_eq!(0.0, v.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn fractional_hilbert_in_frft_domain() {
+        for (n, order) in [(64, 0.5), (64, 1.3), (65, 0.7)] {
+            let mut frft = FrftImpl::new(n);
+            let mut signal = Signal::new(n);
+            let angle = 1.1;
+
+            // a pulse on the positive half of the order `order` domain
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                let u = (i as f32 - (n / 2) as f32 - 12.0) / 4.0;
+                *v = Complex::new(f32::exp(-u * u), 0.0);
+            }
+            frft.process_unitary(&mut signal.time, -order);
+
+            let expected: Vec<_> = signal
+                .time
+                .iter()
+                .map(|v| v * Complex::new(0.0, -angle).exp())
+                .collect();
+
+            signal.hilbert_time(angle, order);
+
+            for (e, r) in expected.iter().zip(signal.time.iter()) {
+                assert_approx_eq!(e.re, r.re, 1e-3);
+                assert_approx_eq!(e.im, r.im, 1e-3);
+            }
+        }
+    }
+}
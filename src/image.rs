@@ -0,0 +1,691 @@
+use crate::Complex;
+use std::f32::consts::PI;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+/// A grayscale or RGB image with samples in [0, 1],
+/// stored row by row with the channels of a pixel interleaved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// 1 for grayscale, 3 for RGB.
+    pub channels: usize,
+    pub data: Vec<f32>,
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+fn quantize(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Whitespace separated tokens of a netpbm header, skipping comments.
+struct Tokens<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Tokens<'b> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b'#' {
+                while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<usize> {
+        self.skip_whitespace();
+
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| invalid("expected a number in netpbm file"))
+    }
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, channels: usize) -> Self {
+        assert!(channels == 1 || channels == 3, "channels must be 1 or 3");
+
+        Self {
+            width,
+            height,
+            channels,
+            data: vec![0.0; width * height * channels],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> &[f32] {
+        let start = (y * self.width + x) * self.channels;
+
+        &self.data[start..start + self.channels]
+    }
+
+    /// Read a netpbm or, with the `png` feature, a PNG file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+
+        if bytes.starts_with(PNG_SIGNATURE) {
+            Self::read_png(&bytes)
+        } else {
+            Self::read_netpbm(&bytes)
+        }
+    }
+
+    /// Write the image in the format given by the extension of `path`:
+    /// binary netpbm for .pgm, .ppm and .pnm or PNG for .png.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+
+        let bytes = match extension.as_deref() {
+            Some("pgm") | Some("ppm") | Some("pnm") => self.to_netpbm(false),
+            Some("png") => self.to_png()?,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "unknown image file extension",
+                ))
+            }
+        };
+
+        fs::write(path, bytes)
+    }
+
+    /// Parse a plain (P2, P3) or binary (P5, P6) netpbm image.
+    /// Binary images with a maximum value above 255 use two bytes per sample.
+    pub fn read_netpbm(bytes: &[u8]) -> Result<Self> {
+        let (channels, plain) = match bytes.get(..2) {
+            Some(b"P2") => (1, true),
+            Some(b"P3") => (3, true),
+            Some(b"P5") => (1, false),
+            Some(b"P6") => (3, false),
+            _ => return Err(invalid("not a P2, P3, P5 or P6 netpbm file")),
+        };
+
+        let mut tokens = Tokens { bytes, pos: 2 };
+        let width = tokens.number()?;
+        let height = tokens.number()?;
+        let max = tokens.number()?;
+
+        if width == 0 || height == 0 {
+            return Err(invalid("netpbm image has no pixels"));
+        }
+
+        if max == 0 || max > 65535 {
+            return Err(invalid("netpbm maximum value out of range"));
+        }
+
+        let truncated = || invalid("netpbm raster is truncated");
+        let len = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| invalid("netpbm image is too large"))?;
+        // checked before allocating, each plain sample takes
+        // at least a digit and a separator
+        let remaining = bytes.len() - tokens.pos;
+
+        let samples = if plain {
+            if len > remaining / 2 {
+                return Err(truncated());
+            }
+
+            (0..len)
+                .map(|_| tokens.number())
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            // exactly one whitespace byte separates the header from the raster
+            let start = tokens.pos + 1;
+            let size = if max > 255 { 2 } else { 1 };
+            let raster = len
+                .checked_mul(size)
+                .and_then(|raster_len| start.checked_add(raster_len))
+                .and_then(|end| bytes.get(start..end))
+                .ok_or_else(truncated)?;
+
+            raster
+                .chunks_exact(size)
+                .map(|s| s.iter().fold(0, |v, &b| v << 8 | b as usize))
+                .collect()
+        };
+
+        if samples.iter().any(|&s| s > max) {
+            return Err(invalid("netpbm sample exceeds the maximum value"));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            channels,
+            data: samples.iter().map(|&s| s as f32 / max as f32).collect(),
+        })
+    }
+
+    /// Encode as 8 bit netpbm, PGM for grayscale and PPM for RGB images,
+    /// in the plain (ASCII) or binary variant.
+    pub fn to_netpbm(&self, plain: bool) -> Vec<u8> {
+        let magic = match (self.channels, plain) {
+            (1, true) => "P2",
+            (1, false) => "P5",
+            (_, true) => "P3",
+            (_, false) => "P6",
+        };
+        let mut bytes = format!("{}\n{} {}\n255\n", magic, self.width, self.height).into_bytes();
+
+        if plain {
+            // netpbm recommends lines of at most 70 characters
+            for line in self.data.chunks(16) {
+                let line = line
+                    .iter()
+                    .map(|&v| quantize(v).to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                bytes.extend(line.bytes());
+                bytes.push(b'\n');
+            }
+        } else {
+            bytes.extend(self.data.iter().map(|&v| quantize(v)));
+        }
+
+        bytes
+    }
+
+    #[cfg(feature = "png")]
+    pub fn read_png(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+
+        if info.width == 0 || info.height == 0 {
+            return Err(invalid("PNG image has no pixels"));
+        }
+        let stride = info.color_type.samples();
+        let channels = if stride < 3 { 1 } else { 3 };
+
+        // alpha is dropped
+        let data = buffer[..info.buffer_size()]
+            .chunks_exact(stride)
+            .flat_map(|pixel| pixel[..channels].iter().map(|&b| b as f32 / 255.0))
+            .collect();
+
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            channels,
+            data,
+        })
+    }
+
+    #[cfg(not(feature = "png"))]
+    pub fn read_png(_bytes: &[u8]) -> Result<Self> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "PNG support requires the `png` feature",
+        ))
+    }
+
+    /// Encode as 8 bit grayscale or RGB PNG.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+
+        encoder.set_color(if self.channels == 1 {
+            png::ColorType::Grayscale
+        } else {
+            png::ColorType::Rgb
+        });
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let samples: Vec<_> = self.data.iter().map(|&v| quantize(v)).collect();
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&samples)?;
+        writer.finish()?;
+
+        Ok(bytes)
+    }
+
+    #[cfg(not(feature = "png"))]
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "PNG support requires the `png` feature",
+        ))
+    }
+
+    /// Load the image into a complex buffer of `width*height` samples,
+    /// with the selected channel as real part.
+    pub fn to_complex(&self, options: &ImportOptions) -> Vec<Complex<f32>> {
+        let mut output = vec![Complex::default(); self.width * self.height];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pixel = self.pixel(x, y);
+                let value = match (options.channel, pixel) {
+                    (_, [gray]) => *gray,
+                    (Channel::Luminance, [r, g, b]) => 0.2126 * r + 0.7152 * g + 0.0722 * b,
+                    (Channel::Red, [r, _, _]) => *r,
+                    (Channel::Green, [_, g, _]) => *g,
+                    (Channel::Blue, [_, _, b]) => *b,
+                    _ => unreachable!(),
+                };
+                let (x, y) = if options.shift {
+                    (
+                        (x + self.width / 2) % self.width,
+                        (y + self.height / 2) % self.height,
+                    )
+                } else {
+                    (x, y)
+                };
+
+                output[y * self.width + x] = Complex::new(value, 0.0);
+            }
+        }
+
+        output
+    }
+}
+
+/// Which channel of a color image is loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// Rec. 709 luma of the RGB values.
+    Luminance,
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ImportOptions {
+    pub channel: Channel,
+    /// Move the top left corner of the image to the center sample,
+    /// where all transforms have their origin.
+    pub shift: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            channel: Channel::Luminance,
+            shift: false,
+        }
+    }
+}
+
+/// The part of a complex buffer that is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    Magnitude,
+    /// Magnitude in decibel, from `ExportOptions::floor_db` (black) to 0 dB.
+    LogMagnitude,
+    /// Phase as hue of a color wheel, magnitude as brightness.
+    Phase,
+    Real,
+    Imaginary,
+}
+
+/// How the values are scaled before they are mapped to colors.
+///
+/// Real and imaginary parts are signed, after `None` and `Max` they are
+/// mapped from [-1, 1] to [0, 1] so that zero is drawn in the middle
+/// of the colormap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Use the values as they are, clipped to the colormap.
+    None,
+    /// Divide by the largest absolute value.
+    Max,
+    /// Stretch the range of the values to the whole colormap.
+    MinMax,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    Gray,
+    /// Black, red, yellow, white.
+    Hot,
+    /// Piecewise linear approximation of matplotlib's viridis.
+    Viridis,
+}
+
+impl Colormap {
+    const VIRIDIS: [[f32; 3]; 5] = [
+        [0.267, 0.005, 0.329],
+        [0.229, 0.322, 0.546],
+        [0.128, 0.567, 0.551],
+        [0.369, 0.789, 0.383],
+        [0.993, 0.906, 0.144],
+    ];
+
+    pub fn color(self, level: f32) -> [f32; 3] {
+        let t = level.clamp(0.0, 1.0);
+
+        match self {
+            Colormap::Gray => [t; 3],
+            Colormap::Hot => [
+                (3.0 * t).min(1.0),
+                (3.0 * t - 1.0).clamp(0.0, 1.0),
+                (3.0 * t - 2.0).max(0.0),
+            ],
+            Colormap::Viridis => {
+                let position = t * (Self::VIRIDIS.len() - 1) as f32;
+                let i = (position as usize).min(Self::VIRIDIS.len() - 2);
+                let f = position - i as f32;
+                let (a, b) = (Self::VIRIDIS[i], Self::VIRIDIS[i + 1]);
+
+                [0, 1, 2].map(|c| a[c] + f * (b[c] - a[c]))
+            }
+        }
+    }
+}
+
+/// Saturated color of `hue` in [0, 1) with brightness `value`.
+fn hsv(hue: f32, value: f32) -> [f32; 3] {
+    let h = 6.0 * hue.rem_euclid(1.0);
+    let x = value * (1.0 - (h % 2.0 - 1.0).abs());
+
+    match h as usize {
+        0 => [value, x, 0.0],
+        1 => [x, value, 0.0],
+        2 => [0.0, value, x],
+        3 => [0.0, x, value],
+        4 => [x, 0.0, value],
+        _ => [value, 0.0, x],
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExportOptions {
+    pub component: Component,
+    pub normalization: Normalization,
+    /// Ignored for the phase, which is always drawn as color wheel.
+    pub colormap: Colormap,
+    /// Lower end of the log magnitude scale.
+    pub floor_db: f32,
+    /// Move the center sample to the top left corner of the image,
+    /// the inverse of `ImportOptions::shift`.
+    pub shift: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            component: Component::Magnitude,
+            normalization: Normalization::Max,
+            colormap: Colormap::Gray,
+            floor_db: -80.0,
+            shift: false,
+        }
+    }
+}
+
+/// Draw a complex buffer of `width*height` samples, stored row by row.
+/// The result is grayscale for the gray colormap and RGB otherwise.
+pub fn export(
+    buffer: &[Complex<f32>],
+    width: usize,
+    height: usize,
+    options: &ExportOptions,
+) -> Image {
+    assert_eq!(width * height, buffer.len(), "buffer does not match size");
+
+    let signed = matches!(options.component, Component::Real | Component::Imaginary);
+    let mut values: Vec<f32> = buffer
+        .iter()
+        .map(|v| match options.component {
+            Component::Real => v.re,
+            Component::Imaginary => v.im,
+            _ => v.norm(),
+        })
+        .collect();
+
+    let (min, max) = values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let largest = f32::max(min.abs(), max.abs());
+
+    for v in values.iter_mut() {
+        *v = match options.normalization {
+            Normalization::None => *v,
+            Normalization::Max if largest > 0.0 => *v / largest,
+            Normalization::MinMax if max > min => (*v - min) / (max - min),
+            _ => 0.0,
+        };
+
+        if signed && options.normalization != Normalization::MinMax {
+            *v = 0.5 * (*v + 1.0);
+        }
+
+        if options.component == Component::LogMagnitude {
+            *v = if *v > 0.0 {
+                1.0 - 20.0 * v.log10() / options.floor_db
+            } else {
+                0.0
+            };
+        }
+    }
+
+    let channels = match (options.component, options.colormap) {
+        (Component::Phase, _) => 3,
+        (_, Colormap::Gray) => 1,
+        _ => 3,
+    };
+    let mut image = Image::new(width, height, channels);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (sx, sy) = if options.shift {
+                ((x + width / 2) % width, (y + height / 2) % height)
+            } else {
+                (x, y)
+            };
+            let i = sy * width + sx;
+            let color = match options.component {
+                Component::Phase => hsv(buffer[i].arg() / (2.0 * PI), values[i].clamp(0.0, 1.0)),
+                _ => options.colormap.color(values[i]),
+            };
+            let start = (y * width + x) * channels;
+
+            image.data[start..start + channels].copy_from_slice(&color[..channels]);
+        }
+    }
+
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::{
+        export, Channel, Colormap, Component, ExportOptions, Image, ImportOptions, Normalization,
+    };
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn netpbm_roundtrip() {
+        for channels in [1, 3] {
+            let mut image = Image::new(5, 3, channels);
+            for (i, v) in image.data.iter_mut().enumerate() {
+                *v = (i * 7 % 256) as f32 / 255.0;
+            }
+
+            for plain in [true, false] {
+                let bytes = image.to_netpbm(plain);
+                assert_eq!(image, Image::read_netpbm(&bytes).unwrap());
+            }
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_roundtrip() {
+        for channels in [1, 3] {
+            let mut image = Image::new(4, 3, channels);
+            for (i, v) in image.data.iter_mut().enumerate() {
+                *v = (i * 11 % 256) as f32 / 255.0;
+            }
+
+            let bytes = image.to_png().unwrap();
+            assert_eq!(image, Image::read_png(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn netpbm_headers() {
+        let plain = b"P2\n# comment\n3 1 # width and height\n4\n0 2\n4\n";
+        let image = Image::read_netpbm(plain).unwrap();
+        assert_eq!((3, 1, 1), (image.width, image.height, image.channels));
+        assert_eq!(vec![0.0, 0.5, 1.0], image.data);
+
+        let wide = b"P5 2 1 65535\n\x80\x00\xff\xff";
+        let image = Image::read_netpbm(wide).unwrap();
+        assert_approx_eq!(32768.0 / 65535.0, image.data[0], 1e-6);
+        assert_approx_eq!(1.0, image.data[1], 1e-6);
+
+        assert!(Image::read_netpbm(b"P7 1 1 255\n\x00").is_err());
+        assert!(Image::read_netpbm(b"P5 2 2 255\n\x00").is_err());
+        assert!(Image::read_netpbm(b"P2 1 1 3\n9").is_err());
+        assert!(Image::read_netpbm(b"P2 0 4 255\n").is_err());
+        assert!(Image::read_netpbm(b"P5 4 0 255\n").is_err());
+
+        // sizes beyond the file or usize fail before allocating
+        assert!(Image::read_netpbm(b"P2 100000 100000 255\n0").is_err());
+        assert!(Image::read_netpbm(b"P6 4294967296 4294967296 255\n\x00").is_err());
+        assert!(Image::read_netpbm(b"P5 9223372036854775807 1 65535\n\x00").is_err());
+    }
+
+    #[test]
+    fn import_channels_and_shift() {
+        let mut image = Image::new(4, 2, 3);
+        image.data[..3].copy_from_slice(&[1.0, 0.5, 0.25]);
+
+        let luminance = image.to_complex(&ImportOptions::default());
+        assert_approx_eq!(0.2126 + 0.7152 * 0.5 + 0.0722 * 0.25, luminance[0].re, 1e-6);
+
+        let options = ImportOptions {
+            channel: Channel::Green,
+            shift: true,
+        };
+        let shifted = image.to_complex(&options);
+        // the top left pixel is moved to the center sample (2, 1)
+        assert_approx_eq!(0.5, shifted[4 + 2].re, 1e-6);
+        assert_eq!(1, shifted.iter().filter(|v| v.re != 0.0).count());
+
+        let back = export(
+            &shifted,
+            4,
+            2,
+            &ExportOptions {
+                normalization: Normalization::None,
+                shift: true,
+                ..Default::default()
+            },
+        );
+        assert_approx_eq!(0.5, back.data[0], 1e-6);
+    }
+
+    #[test]
+    fn export_components() {
+        let buffer = [
+            Complex::new(2.0, 0.0),
+            Complex::new(0.0, -0.2),
+            Complex::new(-1.0, 0.0),
+            Complex::default(),
+        ];
+
+        let magnitude = export(&buffer, 2, 2, &ExportOptions::default());
+        assert_eq!(1, magnitude.channels);
+        assert_eq!(vec![1.0, 0.1, 0.5, 0.0], magnitude.data);
+
+        let log = export(
+            &buffer,
+            2,
+            2,
+            &ExportOptions {
+                component: Component::LogMagnitude,
+                floor_db: -40.0,
+                ..Default::default()
+            },
+        );
+        // 0.1 of the maximum is -20 dB, halfway to the floor
+        assert_approx_eq!(1.0, log.data[0], 1e-6);
+        assert_approx_eq!(0.5, log.data[1], 1e-6);
+        assert_approx_eq!(0.0, log.data[3], 1e-6);
+
+        let real = export(
+            &buffer,
+            2,
+            2,
+            &ExportOptions {
+                component: Component::Real,
+                ..Default::default()
+            },
+        );
+        assert_eq!(vec![1.0, 0.5, 0.25, 0.5], real.data);
+
+        let phase = export(
+            &buffer,
+            2,
+            2,
+            &ExportOptions {
+                component: Component::Phase,
+                ..Default::default()
+            },
+        );
+        assert_eq!(3, phase.channels);
+        // phase 0 is red, phase pi is cyan, zero magnitude is black
+        assert_eq!(&[1.0, 0.0, 0.0], phase.pixel(0, 0));
+        assert_eq!(&[0.0, 0.5, 0.5], phase.pixel(0, 1));
+        assert_eq!(&[0.0, 0.0, 0.0], phase.pixel(1, 1));
+        // phase -pi/2 is violet
+        let violet = phase.pixel(1, 0);
+        assert_approx_eq!(0.1 * 0.5, violet[0], 1e-6);
+        assert_approx_eq!(0.0, violet[1], 1e-6);
+        assert_approx_eq!(0.1, violet[2], 1e-6);
+        assert_approx_eq!(-PI / 2.0, buffer[1].arg(), 1e-6);
+    }
+
+    #[test]
+    fn colormaps() {
+        for colormap in [Colormap::Gray, Colormap::Hot, Colormap::Viridis] {
+            let dark = colormap.color(0.0).iter().sum::<f32>();
+            let bright = colormap.color(1.0).iter().sum::<f32>();
+            assert!(dark < bright);
+            assert_eq!(colormap.color(-1.0), colormap.color(0.0));
+            assert_eq!(colormap.color(2.0), colormap.color(1.0));
+        }
+
+        assert_eq!([1.0, 1.0, 1.0], Colormap::Hot.color(1.0));
+        assert_eq!([0.0, 0.0, 0.0], Colormap::Hot.color(0.0));
+        assert_eq!(Colormap::VIRIDIS[2], Colormap::Viridis.color(0.5));
+    }
+}
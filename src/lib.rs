@@ -1,21 +1,62 @@
-#![feature(iter_array_chunks)]
 #![feature(iter_intersperse)]
 
+pub mod chirp_search;
 pub mod convolver;
+pub mod czt;
+pub mod dfrft;
+pub mod drpe;
+pub mod dtft;
+pub mod dtt;
+pub mod frac_filter;
 pub mod sinc_interp;
 pub mod frft;
 pub mod frft2;
+pub mod hermite;
+pub mod hilbert;
+pub mod image;
+pub mod memory;
+pub mod metrics;
+pub mod multichannel;
+pub mod noise;
+pub mod peaks;
+pub mod plan_cache;
+pub mod psd;
+pub mod radon_wigner;
+pub mod real_fft;
+pub mod rng;
+pub mod scaled_frft;
+pub mod shift;
+pub mod signal2d;
+pub mod window;
 mod iter;
+mod linalg;
 mod sinc;
 mod utils;
 
+use crate::chirp_search::{ChirpEstimate, Concentration};
 use crate::convolver::conv_length;
 use crate::convolver::Convolver;
+use crate::czt::ChirpZ;
+use crate::drpe::{Drpe, DrpeKey};
+use crate::dtft::ContinuousSpectrum;
+use crate::dtt::{FracDtt, Kind as DttKind};
+use crate::frac_filter::Mask;
 #[cfg(not(feature = "frft2"))]
 use crate::frft::Frft as FrftImpl;
 #[cfg(feature = "frft2")]
 use crate::frft2::Frft2 as FrftImpl;
+use crate::hermite::HermiteBasis;
+use crate::hilbert::Hilbert;
 use crate::iter::iter_into_slice;
+use crate::metrics::SignalMetrics;
+use crate::noise::NoiseKind;
+use crate::peaks::{Estimator, Peak, PeakSearch};
+use crate::psd::{Method as PsdMethod, Psd};
+use crate::radon_wigner::RadonWigner;
+use crate::real_fft::RealFft;
+use crate::shift::Shifter;
+use crate::utils::FrftChirps;
+use crate::window::Window;
 use rustfft::Fft;
 use rustfft::{num_complex::Complex, FftPlanner};
 use std::sync::Arc;
@@ -28,40 +69,178 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 #[wasm_bindgen]
 pub struct Signal {
     fft_integer: Arc<dyn Fft<f32>>,
+    ifft_integer: Arc<dyn Fft<f32>>,
     time: Vec<Complex<f32>>,
     freq: Vec<Complex<f32>>,
+    freq_half: Vec<Complex<f32>>,
     frac: Vec<Complex<f32>>,
+    zoom: Vec<Complex<f32>>,
+    spectrum: Vec<Complex<f32>>,
+    surface: Vec<f32>,
+    analytic: Vec<Complex<f32>>,
+    envelope: Vec<f32>,
+    phase: Vec<f32>,
+    inst_freq: Vec<f32>,
+    hermite: Vec<Complex<f32>>,
+    time_axis: Vec<f32>,
+    freq_axis: Vec<f32>,
+    frac_axis: Vec<f32>,
+    sample_rate: f32,
+    start_time: f32,
+    window: Window,
+    windowed: Vec<Complex<f32>>,
+    peaks: Vec<Peak>,
+    psd: Vec<f32>,
+    psd_lower: Vec<f32>,
+    psd_upper: Vec<f32>,
+    psd_dof: f32,
+    psd_estimator: Option<Psd>,
 
     frft: FrftImpl,
+    hilbert: Hilbert,
+    shifter: Shifter,
+    chirp_z: Option<ChirpZ>,
+    continuous: Option<ContinuousSpectrum>,
+    radon_wigner: Option<RadonWigner>,
+    hermite_basis: Option<HermiteBasis>,
+    frac_dtt: Option<FracDtt>,
+    drpe: Option<Drpe>,
+    real_fft: Option<RealFft>,
+    real_buffer: Vec<f32>,
+    scratch: Vec<Complex<f32>>,
 }
 
-fn do_fft(fft: &Arc<dyn Fft<f32>>, source: &Vec<Complex<f32>>, target: &mut Vec<Complex<f32>>) {
+fn do_fft(
+    fft: &Arc<dyn Fft<f32>>,
+    source: &[Complex<f32>],
+    target: &mut [Complex<f32>],
+    scratch: &mut [Complex<f32>],
+) {
+    target.copy_from_slice(source);
+    utils::fft_centered(fft, target, scratch);
+    rescale(source, target);
+}
+
+/// Real valued counterpart of `do_fft` filling both the hermitian
+/// half spectrum `half` and the full centered spectrum `target`.
+fn do_real_fft(
+    fft: &mut RealFft,
+    source: &[Complex<f32>],
+    buffer: &mut [f32],
+    half: &mut [Complex<f32>],
+    target: &mut [Complex<f32>],
+) {
     let len = source.len();
 
-    target.clone_from(source);
-    target.rotate_right(len / 2);
-    fft.process(target);
-    target.rotate_right(len / 2);
-    let scale_nominator = source
-        .iter()
-        .map(|z| z.norm())
-        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-        .unwrap_or(1.0);
-    let scale_denom = target
+    for (i, v) in buffer.iter_mut().enumerate() {
+        *v = source[(i + len / 2) % len].re;
+    }
+
+    fft.process(buffer, half);
+
+    for (i, v) in target.iter_mut().enumerate() {
+        let k = (i + len / 2) % len;
+        *v = if k <= len / 2 {
+            half[k]
+        } else {
+            half[len - k].conj()
+        };
+    }
+
+    let scale = rescale(source, target);
+
+    for v in half.iter_mut() {
+        *v *= scale;
+    }
+}
+
+/// Scale `target` so that its largest magnitude matches that of `reference`,
+/// returning the applied factor.
+fn rescale(reference: &[Complex<f32>], target: &mut [Complex<f32>]) -> f32 {
+    let scale = scale_ratio(max_norm(reference), max_norm(target));
+
+    for v in target.iter_mut() {
+        *v *= scale;
+    }
+
+    scale
+}
+
+fn max_norm(values: &[Complex<f32>]) -> f32 {
+    values
         .iter()
         .map(|z| z.norm())
         .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-        .unwrap_or(1.0);
+        .unwrap_or(1.0)
+}
 
-    let scale = if scale_denom != 0.0 {
+fn scale_ratio(scale_nominator: f32, scale_denom: f32) -> f32 {
+    if scale_denom != 0.0 {
         scale_nominator / scale_denom
     } else {
         1.0
+    }
+}
+
+fn is_real(signal: &[Complex<f32>]) -> bool {
+    signal.iter().all(|z| z.im == 0.0)
+}
+
+fn prepare_chirp_z<'c>(
+    cache: &'c mut Option<ChirpZ>,
+    target: &mut Vec<Complex<f32>>,
+    length: usize,
+    points: usize,
+) -> &'c mut ChirpZ {
+    memory::resize_view(target, points, Complex::default());
+
+    if cache.as_ref().map(ChirpZ::points) != Some(points) {
+        *cache = Some(ChirpZ::new(length, points));
+    }
+
+    cache.as_mut().unwrap()
+}
+
+/// The basis of the first `count` Hermite-Gaussians, recomputed
+/// only when `count` changes.
+fn prepare_hermite<'b>(
+    cache: &'b mut Option<HermiteBasis>,
+    target: &mut Vec<Complex<f32>>,
+    length: usize,
+    count: usize,
+) -> &'b HermiteBasis {
+    let count = count.min(length);
+    memory::resize_view(target, count, Complex::default());
+
+    if cache.as_ref().map(HermiteBasis::count) != Some(count) {
+        *cache = Some(HermiteBasis::new(length, count));
+    }
+
+    cache.as_ref().unwrap()
+}
+
+/// The cached DRPE of `length` samples, switched to the key of `seed`,
+/// `order_a` and `order_b`. Only the masks are regenerated, the
+/// transform is kept.
+fn prepare_drpe(
+    cache: &mut Option<Drpe>,
+    length: usize,
+    seed: u32,
+    order_a: f32,
+    order_b: f32,
+) -> &mut Drpe {
+    let key = DrpeKey {
+        seed: seed as u64,
+        order_a,
+        order_b,
     };
 
-    for v in target.iter_mut() {
-        *v *= scale;
+    match cache.as_mut() {
+        Some(drpe) => drpe.set_key(key),
+        None => *cache = Some(Drpe::new(length, key)),
     }
+
+    cache.as_mut().unwrap()
 }
 
 #[wasm_bindgen]
@@ -72,20 +251,63 @@ impl Signal {
         let sinc_len = 2 * length - 1;
         let _fft_conv_len = conv_length(length, sinc_len);
 
-        let mut planner = FftPlanner::new();
-        let fft_integer = planner.plan_fft_forward(length);
+        let fft_integer = plan_cache::plan_fft_forward(length);
+        let ifft_integer = plan_cache::plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_integer.get_inplace_scratch_len(),
+            ifft_integer.get_inplace_scratch_len(),
+        );
+        let scratch = vec![Complex::default(); scratch_len];
 
         let time = vec![Complex::default(); length];
         let freq = vec![Complex::default(); length];
         let frac = vec![Complex::default(); length];
+        let real_fft = (length > 0 && length.is_multiple_of(2)).then(|| RealFft::new(length));
 
-        Self {
+        let mut signal = Self {
             fft_integer,
+            ifft_integer,
             frft: FrftImpl::new(length),
+            hilbert: Hilbert::new(length),
+            shifter: Shifter::new(length),
+            chirp_z: None,
+            continuous: None,
+            radon_wigner: None,
+            hermite_basis: None,
+            frac_dtt: None,
+            drpe: None,
+            real_fft,
+            real_buffer: vec![0.0; length],
+            scratch,
             time,
             freq,
+            freq_half: vec![Complex::default(); RealFft::result_len(length)],
             frac,
-        }
+            zoom: Vec::new(),
+            spectrum: Vec::new(),
+            surface: Vec::new(),
+            analytic: vec![Complex::default(); length],
+            envelope: vec![0.0; length],
+            phase: vec![0.0; length],
+            inst_freq: vec![0.0; length],
+            hermite: Vec::new(),
+            time_axis: vec![0.0; length],
+            freq_axis: vec![0.0; length],
+            frac_axis: vec![0.0; length],
+            sample_rate: 1.0,
+            start_time: 0.0,
+            window: Window::Rectangular,
+            windowed: vec![Complex::default(); length],
+            peaks: Vec::new(),
+            psd: Vec::new(),
+            psd_lower: Vec::new(),
+            psd_upper: Vec::new(),
+            psd_dof: 0.0,
+            psd_estimator: None,
+        };
+
+        signal.update_axes();
+        signal
     }
 
     pub fn get_time(&self) -> *const Complex<f32> {
@@ -96,87 +318,1173 @@ impl Signal {
         self.freq.as_ptr()
     }
 
+    /// Non-negative half of the spectrum of a real time signal,
+    /// bin 0 to bin N/2, as computed by `update_freq_real`.
+    pub fn get_freq_half(&self) -> *const Complex<f32> {
+        self.freq_half.as_ptr()
+    }
+
     pub fn get_frac(&self) -> *const Complex<f32> {
         self.frac.as_ptr()
     }
 
+    pub fn get_zoom(&self) -> *const Complex<f32> {
+        self.zoom.as_ptr()
+    }
+
+    pub fn get_spectrum(&self) -> *const Complex<f32> {
+        self.spectrum.as_ptr()
+    }
+
+    /// Radon-Wigner surface as computed by `update_radon_wigner`,
+    /// one row of `get_len()` values per order.
+    pub fn get_radon_wigner(&self) -> *const f32 {
+        self.surface.as_ptr()
+    }
+
+    pub fn get_analytic(&self) -> *const Complex<f32> {
+        self.analytic.as_ptr()
+    }
+
+    pub fn get_envelope(&self) -> *const f32 {
+        self.envelope.as_ptr()
+    }
+
+    pub fn get_phase(&self) -> *const f32 {
+        self.phase.as_ptr()
+    }
+
+    pub fn get_inst_freq(&self) -> *const f32 {
+        self.inst_freq.as_ptr()
+    }
+
+    pub fn get_hermite(&self) -> *const Complex<f32> {
+        self.hermite.as_ptr()
+    }
+
+    /// Time of each sample in seconds.
+    pub fn get_time_axis(&self) -> *const f32 {
+        self.time_axis.as_ptr()
+    }
+
+    /// Frequency of each bin of the centered spectrum in Hz.
+    pub fn get_freq_axis(&self) -> *const f32 {
+        self.freq_axis.as_ptr()
+    }
+
+    /// Coordinate of each sample of a fractional domain in seconds,
+    /// relative to the center sample.
+    ///
+    /// The FRFT grid with spacing 1/sqrt(N) is scaled by
+    /// s = sqrt(N)/sample_rate, so order 0 is the time relative to the
+    /// center and at order 1 a coordinate u is the frequency u/s^2.
+    pub fn get_frac_axis(&self) -> *const f32 {
+        self.frac_axis.as_ptr()
+    }
+
+    pub fn get_sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    pub fn get_start_time(&self) -> f32 {
+        self.start_time
+    }
+
+    /// Set the sample rate in Hz, which relabels the axes
+    /// without changing any samples.
+    ///
+    /// Returns false and keeps the current rate unless `sample_rate`
+    /// is positive and finite.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) -> bool {
+        if !(sample_rate.is_finite() && sample_rate > 0.0) {
+            return false;
+        }
+
+        self.sample_rate = sample_rate;
+        self.update_axes();
+        true
+    }
+
+    /// Set the time of the first sample in seconds.
+    pub fn set_start_time(&mut self, start_time: f32) {
+        self.start_time = start_time;
+        self.update_axes();
+    }
+
+    /// Time in seconds of the strongest sample of the time signal.
+    pub fn time_peak(&self) -> f32 {
+        let center = (self.time.len() / 2) as f32;
+
+        self.start_time + (center + chirp_search::peak_position(&self.time)) / self.sample_rate
+    }
+
+    /// Frequency in Hz of the strongest bin of the freq view.
+    pub fn freq_peak(&self) -> f32 {
+        chirp_search::peak_position(&self.freq) * self.sample_rate / self.freq.len() as f32
+    }
+
+    /// Coordinate in seconds of the strongest sample of the frac view,
+    /// on the scale of `get_frac_axis`.
+    pub fn frac_peak(&self) -> f32 {
+        chirp_search::peak_position(&self.frac) / self.sample_rate
+    }
+
+    pub fn get_window(&self) -> Window {
+        self.window
+    }
+
+    /// Select the window applied before the spectral peak search.
+    pub fn set_window(&mut self, window: Window) {
+        self.window = window;
+    }
+
+    pub fn get_peaks_len(&self) -> usize {
+        self.peaks.len()
+    }
+
+    /// Peak `index` of the last `update_peaks`, strongest first,
+    /// or `None` past `get_peaks_len`.
+    pub fn get_peak(&self, index: usize) -> Option<Peak> {
+        self.peaks.get(index).copied()
+    }
+
+    /// Power spectral density in squared units per Hz as computed by
+    /// one of the `update_psd_*` methods, centered like the freq view.
+    pub fn get_psd(&self) -> *const f32 {
+        self.psd.as_ptr()
+    }
+
+    /// Lower bound of the confidence interval of the density.
+    pub fn get_psd_lower(&self) -> *const f32 {
+        self.psd_lower.as_ptr()
+    }
+
+    /// Upper bound of the confidence interval of the density.
+    pub fn get_psd_upper(&self) -> *const f32 {
+        self.psd_upper.as_ptr()
+    }
+
+    pub fn get_psd_len(&self) -> usize {
+        self.psd.len()
+    }
+
+    /// Width of a bin of the density in Hz.
+    pub fn get_psd_resolution(&self) -> f32 {
+        self.sample_rate / self.psd.len().max(1) as f32
+    }
+
+    /// Equivalent degrees of freedom of the density estimate.
+    pub fn get_psd_dof(&self) -> f32 {
+        self.psd_dof
+    }
+
     pub fn get_len(&self) -> usize {
         self.time.len()
     }
 
+    pub fn get_freq_half_len(&self) -> usize {
+        self.freq_half.len()
+    }
+
+    pub fn get_zoom_len(&self) -> usize {
+        self.zoom.len()
+    }
+
+    pub fn get_spectrum_len(&self) -> usize {
+        self.spectrum.len()
+    }
+
+    pub fn get_hermite_len(&self) -> usize {
+        self.hermite.len()
+    }
+
+    pub fn get_radon_wigner_len(&self) -> usize {
+        self.surface.len()
+    }
+
+    /// Compute the spectrum, using the real valued fast path
+    /// if the time signal has no imaginary part.
     pub fn update_freq(&mut self) {
-        do_fft(&self.fft_integer, &self.time, &mut self.freq);
+        if is_real(&self.time) {
+            self.update_freq_real();
+        } else {
+            do_fft(&self.fft_integer, &self.time, &mut self.freq, &mut self.scratch);
+        }
+    }
+
+    /// Compute the spectrum of the real part of the time signal
+    /// via a half length complex FFT.
+    ///
+    /// Falls back to the complex FFT for signals of odd length.
+    pub fn update_freq_real(&mut self) {
+        match &mut self.real_fft {
+            Some(fft) => do_real_fft(
+                fft,
+                &self.time,
+                &mut self.real_buffer,
+                &mut self.freq_half,
+                &mut self.freq,
+            ),
+            None => do_fft(&self.fft_integer, &self.time, &mut self.freq, &mut self.scratch),
+        }
     }
 
     pub fn update_freq_with_cepstrum(&mut self, interp: f32) {
-        do_fft(&self.fft_integer, &self.time, &mut self.freq);
+        do_fft(&self.fft_integer, &self.time, &mut self.freq, &mut self.scratch);
 
-        let scale_nominator = self
-            .freq
-            .iter()
-            .cloned()
-            .map(|z| z.norm())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-            .unwrap_or(1.0);
+        let mut scale_nominator: f32 = 0.0;
+        let mut scale_denom: f32 = 0.0;
 
         for bin in &mut self.freq {
-            *bin = *bin * (1.0-interp) + interp * Complex::new(bin.norm().max(0.0001).ln(), bin.arg());
+            let norm = bin.norm();
+            *bin = *bin * (1.0 - interp) + interp * Complex::new(norm.max(0.0001).ln(), bin.arg());
+
+            scale_nominator = scale_nominator.max(norm);
+            scale_denom = scale_denom.max(bin.norm());
+        }
+
+        let scale = scale_ratio(scale_nominator, scale_denom);
+
+        for v in self.freq.iter_mut() {
+            *v *= scale;
+        }
+
+        do_fft(&self.ifft_integer, &self.freq, &mut self.time, &mut self.scratch);
+    }
+
+    pub fn update_time(&mut self) {
+        do_fft(&self.ifft_integer, &self.freq, &mut self.time, &mut self.scratch);
+    }
+
+    /// Reconstruct a real time signal from the non-negative frequencies
+    /// of the spectrum, which are copied to the half spectrum first.
+    ///
+    /// Falls back to `update_time` for signals of odd length.
+    pub fn update_time_real(&mut self) {
+        let len = self.time.len();
+
+        match &mut self.real_fft {
+            Some(fft) => {
+                for (k, v) in self.freq_half.iter_mut().enumerate() {
+                    *v = self.freq[(k + len / 2) % len];
+                }
+
+                fft.process_inverse(&self.freq_half, &mut self.real_buffer);
+
+                for (i, v) in self.time.iter_mut().enumerate() {
+                    *v = Complex::new(self.real_buffer[(i + len / 2) % len], 0.0);
+                }
+
+                rescale(&self.freq_half, &mut self.time);
+            }
+            None => self.update_time(),
+        }
+    }
+
+    /// Add reproducible noise generated from `seed` to the time signal,
+    /// with a power exactly `snr_db` below that of the signal, or below
+    /// unit power if the signal is silent.
+    ///
+    /// The signal is left as it is if the noise has no power to scale,
+    /// like pink and brown noise of a single sample, which have no
+    /// component besides DC.
+    pub fn add_noise(&mut self, kind: NoiseKind, seed: u32, snr_db: f32) {
+        let mut noise = vec![Complex::default(); self.time.len()];
+        noise::fill(kind, seed as u64, &mut noise);
+
+        let noise_power = noise::power(&noise);
+        if noise_power == 0.0 {
+            return;
         }
 
-        let scale_denom = self
-            .freq
+        let power = noise::power(&self.time);
+        let reference = if power > 0.0 { power } else { 1.0 };
+        let scale = f32::sqrt(reference * 10f32.powf(-snr_db / 10.0) / noise_power);
+
+        for (v, n) in self.time.iter_mut().zip(&noise) {
+            *v += n * scale;
+        }
+    }
+
+    /// Circularly shift the time signal by a possibly fractional number of samples.
+    pub fn shift_time(&mut self, delay: f32) {
+        self.shifter.shift_circular(&mut self.time, delay);
+    }
+
+    /// Delay the time signal by a possibly fractional number of samples
+    /// without wrapping around, using a windowed sinc filter.
+    pub fn delay_time(&mut self, delay: f32, half_width: usize) {
+        self.shifter.delay_linear(&mut self.time, delay, half_width);
+    }
+
+    /// Shift the spectrum of the time signal by a possibly fractional number of bins.
+    pub fn shift_freq(&mut self, bins: f32) {
+        shift::modulate(&mut self.time, bins);
+    }
+
+    /// Evaluate the spectrum at `points` frequencies between `from` and `to`,
+    /// measured in (fractional) bins, to zoom into a region of the freq view.
+    pub fn update_zoom(&mut self, from: f32, to: f32, points: usize) {
+        let czt = prepare_chirp_z(&mut self.chirp_z, &mut self.zoom, self.time.len(), points);
+        czt.zoom(&self.time, &mut self.zoom, from, to);
+    }
+
+    /// Evaluate the z-transform on the spiral contour starting at
+    /// `start_radius * e^(i*start_angle)` and advancing by the inverse of
+    /// `ratio_radius * e^(i*ratio_angle)` for each of the `points` outputs.
+    pub fn update_chirp_z(
+        &mut self,
+        start_radius: f32,
+        start_angle: f32,
+        ratio_radius: f32,
+        ratio_angle: f32,
+        points: usize,
+    ) {
+        let start = Complex::from_polar(start_radius, start_angle);
+        let ratio = Complex::from_polar(ratio_radius, ratio_angle);
+
+        let czt = prepare_chirp_z(&mut self.chirp_z, &mut self.zoom, self.time.len(), points);
+        czt.process(&self.time, &mut self.zoom, start, ratio);
+    }
+
+    /// Evaluate the spectrum at the given (fractional) bins with the
+    /// Goertzel algorithm, returning interleaved real and imaginary parts.
+    pub fn goertzel(&self, freqs: &[f32]) -> Vec<f32> {
+        freqs
             .iter()
-            .cloned()
-            .map(|z| z.norm())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-            .unwrap_or(1.0);
+            .map(|f| dtft::goertzel(&self.time, *f))
+            .flat_map(|z| [z.re, z.im])
+            .collect()
+    }
 
+    /// Evaluate the spectrum at the given (fractional) bins by direct
+    /// summation, returning interleaved real and imaginary parts.
+    pub fn dtft(&self, freqs: &[f32]) -> Vec<f32> {
+        freqs
+            .iter()
+            .map(|f| dtft::dtft(&self.time, *f))
+            .flat_map(|z| [z.re, z.im])
+            .collect()
+    }
 
-        let scale = if scale_denom != 0.0 {
-            scale_nominator / scale_denom
-        } else {
-            1.0
-        };
+    /// Sample the continuous spectrum of the time signal at
+    /// `oversampling` points per bin, at least one.
+    pub fn update_spectrum(&mut self, oversampling: usize) {
+        let length = self.time.len();
+        let oversampling = oversampling.max(1);
 
-        for v in self.freq.iter_mut() {
-            *v *= scale;
+        if self.continuous.as_ref().map(ContinuousSpectrum::oversampling) != Some(oversampling) {
+            self.continuous = Some(ContinuousSpectrum::new(length, oversampling));
         }
 
-        do_fft(&self.fft_integer, &self.freq, &mut self.time);
+        let continuous = self.continuous.as_mut().unwrap();
+        memory::resize_view(&mut self.spectrum, continuous.result_len(), Complex::default());
+        continuous.process(&self.time, &mut self.spectrum);
+    }
+
+    /// Compute |F^a x|^2 of the time signal for `orders` equally spaced
+    /// orders a in [0, 2), optionally in decibel relative to the maximum.
+    pub fn update_radon_wigner(&mut self, orders: usize, log: bool) {
+        let length = self.time.len();
 
-        self.time.reverse();
+        if self.radon_wigner.as_ref().map(RadonWigner::orders) != Some(orders) {
+            self.radon_wigner = Some(RadonWigner::new(&self.frft, length, orders));
+        }
+
+        let radon_wigner = self.radon_wigner.as_mut().unwrap();
+        memory::resize_view(&mut self.surface, radon_wigner.result_len(), 0.0);
+        radon_wigner.process(&mut self.frft, &self.time, &mut self.surface, log);
     }
 
-    pub fn update_time(&mut self) {
-        do_fft(&self.fft_integer, &self.freq, &mut self.time);
+    /// Decompose the time signal into the coefficients of the
+    /// first `count` discrete Hermite-Gaussians.
+    pub fn update_hermite(&mut self, count: usize) {
+        let length = self.time.len();
+        let basis = prepare_hermite(&mut self.hermite_basis, &mut self.hermite, length, count);
+
+        basis.decompose(&self.time, &mut self.hermite);
+    }
+
+    /// Replace the time signal by the sum of discrete Hermite-Gaussians
+    /// weighted by `coefficients`, given as pairs of real and imaginary
+    /// part in increasing order, which are kept in the hermite view.
+    pub fn synthesize_hermite(&mut self, coefficients: &[f32]) {
+        let length = self.time.len();
+        let count = coefficients.len() / 2;
+        let basis = prepare_hermite(&mut self.hermite_basis, &mut self.hermite, length, count);
+
+        for (c, pair) in self.hermite.iter_mut().zip(coefficients.chunks_exact(2)) {
+            *c = Complex::new(pair[0], pair[1]);
+        }
+
+        basis.synthesize(&self.hermite, &mut self.time);
     }
 
     pub fn update_frac(&mut self, fraction: f32) {
-        self.frac.clone_from(&self.time);
+        self.frac.copy_from_slice(&self.time);
         self.frft.process(&mut self.frac, fraction);
 
-        let scale_nominator = self
-            .time
-            .iter()
-            .cloned()
-            .map(|z| z.norm())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-            .unwrap_or(1.0);
-        let scale_denom = self
-            .frac
-            .iter()
-            .cloned()
-            .map(|z| z.norm())
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Less))
-            .unwrap_or(1.0);
-        let scale = if scale_denom != 0.0 {
-            scale_nominator / scale_denom
+        rescale(&self.time, &mut self.frac);
+    }
+
+    /// Fractional cosine or sine transform of the time signal into frac,
+    /// see `dtt::FracDtt`.
+    ///
+    /// Unlike the other domains the transforms start at sample 0.
+    pub fn update_frac_dtt(&mut self, kind: DttKind, fraction: f32) {
+        let length = self.time.len();
+
+        if self.frac_dtt.as_ref().map(FracDtt::kind) != Some(kind) {
+            self.frac_dtt = Some(FracDtt::new(kind, length));
+        }
+
+        self.frac.copy_from_slice(&self.time);
+        self.frac_dtt
+            .as_mut()
+            .unwrap()
+            .process(&mut self.frac, fraction);
+
+        rescale(&self.time, &mut self.frac);
+    }
+
+    /// Encrypt the time signal into frac by double random phase encoding
+    /// with the masks generated from `seed` and the fractional orders
+    /// `order_a` and `order_b`, see `drpe::Drpe`.
+    ///
+    /// Unlike the other domains frac is not rescaled, so that
+    /// `decrypt_drpe` with the same key restores the time signal.
+    pub fn encrypt_drpe(&mut self, seed: u32, order_a: f32, order_b: f32) {
+        let drpe = prepare_drpe(&mut self.drpe, self.time.len(), seed, order_a, order_b);
+
+        self.frac.copy_from_slice(&self.time);
+        drpe.encrypt(&mut self.frac);
+    }
+
+    /// Decrypt frac into the time signal, the inverse of `encrypt_drpe`
+    /// if the key matches.
+    pub fn decrypt_drpe(&mut self, seed: u32, order_a: f32, order_b: f32) {
+        let drpe = prepare_drpe(&mut self.drpe, self.time.len(), seed, order_a, order_b);
+
+        self.time.copy_from_slice(&self.frac);
+        drpe.decrypt(&mut self.time);
+    }
+
+    /// Compute the analytic signal of the real part of the time signal,
+    /// its envelope, unwrapped phase and instantaneous frequency in bins.
+    ///
+    /// The freq view is replaced by the one-sided spectrum
+    /// the analytic signal is synthesized from.
+    pub fn update_analytic(&mut self) {
+        self.hilbert
+            .analytic(&self.time, &mut self.freq, &mut self.analytic);
+        rescale(&self.time, &mut self.freq);
+
+        hilbert::envelope(&self.analytic, &mut self.envelope);
+        hilbert::unwrapped_phase(&self.analytic, &mut self.phase);
+        hilbert::instantaneous_frequency(&self.phase, &mut self.inst_freq);
+    }
+
+    /// Apply the fractional Hilbert transform to the time signal, phase
+    /// shifting the positive half of the order `order` fractional domain
+    /// by `-angle` and the negative half by `angle`.
+    ///
+    /// Order 1 is the fractional Hilbert transform of the spectrum,
+    /// which for `angle = pi/2` is the conventional Hilbert transform.
+    pub fn hilbert_time(&mut self, angle: f32, order: f32) {
+        self.frft.process_unitary(&mut self.time, order);
+        hilbert::rotate_half_planes(&mut self.time, angle);
+        self.frft.process_unitary(&mut self.time, -order);
+    }
+
+    /// Estimate the chirp rate and frequency of the time signal from the
+    /// fractional domain in which it is most concentrated, which is left
+    /// in the frac view.
+    pub fn estimate_chirp(&mut self, concentration: Concentration) -> ChirpEstimate {
+        let (order, score) =
+            chirp_search::find_order(&mut self.frft, &self.time, &mut self.frac, concentration);
+        let position = chirp_search::peak_position(&self.frac);
+
+        rescale(&self.time, &mut self.frac);
+
+        ChirpEstimate::new(self.time.len(), self.sample_rate, order, position, score)
+    }
+
+    /// Find the `count` strongest peaks of the spectrum of the time
+    /// signal weighted by the selected window, refined to fractions of a
+    /// bin by `estimator`, with amplitude and phase corrected for the
+    /// window.
+    ///
+    /// Peaks more than `threshold_db` below the strongest bin or closer
+    /// than `min_separation` bins to a stronger peak are skipped.
+    pub fn update_peaks(
+        &mut self,
+        count: usize,
+        threshold_db: f32,
+        min_separation: f32,
+        estimator: Estimator,
+    ) {
+        let search = PeakSearch {
+            count,
+            threshold_db,
+            min_separation,
+            estimator,
+            window: self.window,
+        };
+
+        self.update_windowed();
+        self.peaks = search.find(&self.windowed, self.sample_rate);
+    }
+
+    /// SNR, THD of the first `harmonics` harmonics, SINAD, SFDR and ENOB
+    /// of the strongest tone of the time signal, from its spectrum under
+    /// the selected window.
+    pub fn metrics(&mut self, harmonics: usize) -> SignalMetrics {
+        self.update_windowed();
+        metrics::analyze(&self.windowed, self.window, self.sample_rate, harmonics)
+    }
+
+    /// Estimate the power spectral density of the time signal by a single
+    /// spectrum under `window`, with the `confidence` (e.g. 0.95) interval.
+    ///
+    /// Returns whether the density was estimated, like the other
+    /// `update_psd_*` methods.
+    pub fn update_psd_periodogram(&mut self, window: Window, confidence: f32) -> bool {
+        self.update_psd(PsdMethod::Periodogram { window }, confidence)
+    }
+
+    /// Estimate the power spectral density of the time signal by averaging
+    /// the spectra of windowed segments of `segment` samples overlapping
+    /// by `overlap` samples.
+    ///
+    /// Returns false and leaves the density empty unless the segments fit
+    /// the signal and the overlap is shorter than a segment.
+    pub fn update_psd_welch(
+        &mut self,
+        segment: usize,
+        overlap: usize,
+        window: Window,
+        confidence: f32,
+    ) -> bool {
+        let method = PsdMethod::Welch {
+            segment,
+            overlap,
+            window,
+        };
+        self.update_psd(method, confidence)
+    }
+
+    /// Estimate the power spectral density of the time signal by averaging
+    /// the spectra under `tapers` Slepian tapers of time-bandwidth product
+    /// `bandwidth`.
+    ///
+    /// Returns false and leaves the density empty without a taper
+    /// or a positive bandwidth.
+    pub fn update_psd_multitaper(&mut self, bandwidth: f32, tapers: usize, confidence: f32) -> bool {
+        self.update_psd(PsdMethod::Multitaper { bandwidth, tapers }, confidence)
+    }
+
+    /// Keep only the samples between `from` and `to` of the order
+    /// `fraction` fractional domain of the time signal.
+    pub fn filter_frac_band_pass(&mut self, fraction: f32, from: f32, to: f32) {
+        self.filter_frac(fraction, Mask::BandPass { from, to });
+    }
+
+    /// Remove the samples between `from` and `to` of the order
+    /// `fraction` fractional domain of the time signal.
+    pub fn filter_frac_notch(&mut self, fraction: f32, from: f32, to: f32) {
+        self.filter_frac(fraction, Mask::Notch { from, to });
+    }
+
+    /// Weight each sample of the order `fraction` fractional domain
+    /// of the time signal, samples without a weight are removed.
+    pub fn filter_frac_weights(&mut self, fraction: f32, weights: &[f32]) {
+        self.filter_frac(fraction, Mask::Weights(weights));
+    }
+
+    /// Change the number of samples, replanning all transforms and
+    /// reallocating all buffers.
+    ///
+    /// The time signal is resampled to the new length if `resample` is set,
+    /// otherwise it is zero padded or truncated around its center.
+    /// Resampling scales the sample rate so the duration stays the same.
+    /// The start time, window, zoom, spectrum and density settings are
+    /// kept, the other domains have to be updated again. Views into the
+    /// old buffers become invalid, which is signaled by `memory_generation`.
+    ///
+    /// Returns false and keeps the signal as it is for lengths below
+    /// `MIN_LEN`.
+    pub fn set_len(&mut self, length: usize, resample: bool) -> bool {
+        if length < Self::MIN_LEN {
+            return false;
+        }
+        if length == self.time.len() {
+            return true;
+        }
+
+        let mut resized = Signal::new(length);
+        resized.sample_rate = self.sample_rate;
+        resized.start_time = self.start_time;
+        resized.window = self.window;
+
+        if resample {
+            utils::resample_centered(&self.time, &mut resized.time);
+            resized.sample_rate *= length as f32 / self.time.len() as f32;
         } else {
-            1.0
+            utils::pad_centered(&self.time, &mut resized.time);
+        }
+
+        resized.update_axes();
+
+        if let Some(czt) = &self.chirp_z {
+            let czt = ChirpZ::new(length, czt.points());
+            resized.zoom.resize(czt.points(), Complex::default());
+            resized.chirp_z = Some(czt);
+        }
+
+        if let Some(continuous) = &self.continuous {
+            let continuous = ContinuousSpectrum::new(length, continuous.oversampling());
+            resized
+                .spectrum
+                .resize(continuous.result_len(), Complex::default());
+            resized.continuous = Some(continuous);
+        }
+
+        if let Some(radon_wigner) = &self.radon_wigner {
+            let radon_wigner = RadonWigner::new(&resized.frft, length, radon_wigner.orders());
+            resized.surface.resize(radon_wigner.result_len(), 0.0);
+            resized.radon_wigner = Some(radon_wigner);
+        }
+
+        if let Some(frac_dtt) = &self.frac_dtt {
+            resized.frac_dtt = Some(FracDtt::new(frac_dtt.kind(), length));
+        }
+
+        if let Some(drpe) = &self.drpe {
+            resized.drpe = Some(Drpe::new(length, drpe.key()));
+        }
+
+        if let Some(estimator) = &self.psd_estimator {
+            let method = estimator.method();
+
+            if method.is_valid(length) {
+                let estimator = Psd::new(length, method);
+                resized.psd.resize(estimator.result_len(), 0.0);
+                resized.psd_lower.resize(estimator.result_len(), 0.0);
+                resized.psd_upper.resize(estimator.result_len(), 0.0);
+                resized.psd_estimator = Some(estimator);
+            }
+        }
+
+        if let Some(basis) = &self.hermite_basis {
+            let basis = HermiteBasis::new(length, basis.count());
+            resized.hermite.resize(basis.count(), Complex::default());
+            resized.hermite_basis = Some(basis);
+        }
+
+        *self = resized;
+        memory::invalidate_views();
+        true
+    }
+}
+
+impl Signal {
+    /// Shortest signal the transforms support.
+    pub const MIN_LEN: usize = 2;
+
+    pub fn time(&self) -> &[Complex<f32>] {
+        &self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut [Complex<f32>] {
+        &mut self.time
+    }
+
+    pub fn freq(&self) -> &[Complex<f32>] {
+        &self.freq
+    }
+
+    pub fn frac(&self) -> &[Complex<f32>] {
+        &self.frac
+    }
+
+    fn update_psd(&mut self, method: PsdMethod, confidence: f32) -> bool {
+        let length = self.time.len();
+
+        if !method.is_valid(length) {
+            self.psd_estimator = None;
+            self.psd.clear();
+            self.psd_lower.clear();
+            self.psd_upper.clear();
+            self.psd_dof = 0.0;
+            return false;
+        }
+
+        if self.psd_estimator.as_ref().map(Psd::method) != Some(method) {
+            self.psd_estimator = Some(Psd::new(length, method));
+        }
+
+        let estimator = self.psd_estimator.as_mut().unwrap();
+        let bins = estimator.result_len();
+        memory::resize_view(&mut self.psd, bins, 0.0);
+        memory::resize_view(&mut self.psd_lower, bins, 0.0);
+        memory::resize_view(&mut self.psd_upper, bins, 0.0);
+
+        estimator.process(&self.time, self.sample_rate, &mut self.psd);
+        self.psd_dof = estimator.degrees_of_freedom(length);
+
+        psd::confidence_bounds(
+            &self.psd,
+            self.psd_dof,
+            confidence,
+            &mut self.psd_lower,
+            &mut self.psd_upper,
+        );
+        true
+    }
+
+    /// Unscaled spectrum of the time signal weighted by the selected window.
+    fn update_windowed(&mut self) {
+        self.windowed.copy_from_slice(&self.time);
+        self.window.apply(&mut self.windowed);
+        utils::fft_centered(&self.fft_integer, &mut self.windowed, &mut self.scratch);
+    }
+
+    fn update_axes(&mut self) {
+        let len = self.time.len();
+        let center = (len / 2) as f32;
+        let interval = 1.0 / self.sample_rate;
+
+        for (i, t) in self.time_axis.iter_mut().enumerate() {
+            *t = self.start_time + i as f32 * interval;
+        }
+        for (k, f) in self.freq_axis.iter_mut().enumerate() {
+            *f = (k as f32 - center) * self.sample_rate / len as f32;
+        }
+        for (k, u) in self.frac_axis.iter_mut().enumerate() {
+            *u = (k as f32 - center) * interval;
+        }
+    }
+
+    /// Transform the time signal to the order `fraction` domain, apply
+    /// `mask` and transform back. The masked fractional domain is
+    /// kept in the frac view.
+    fn filter_frac(&mut self, fraction: f32, mask: Mask) {
+        self.frft.process_unitary(&mut self.time, fraction);
+        mask.apply(&mut self.time);
+
+        self.frac.copy_from_slice(&self.time);
+        self.frft.process_unitary(&mut self.time, -fraction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::memory_generation;
+    use crate::noise::{self, NoiseKind};
+    use crate::peaks::Estimator;
+    use crate::rng::SplitMix64;
+    use crate::window::Window;
+    use crate::{do_fft, is_real, Complex, DttKind, Signal};
+    use std::f32::consts::PI;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn real_signal(length: usize) -> Signal {
+        let mut signal = Signal::new(length);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            let x = i as f32 - (length / 2) as f32;
+            *v = Complex::new(f32::exp(-x * x / 20.0) + 0.2 * (x * 0.7).cos(), 0.0);
+        }
+
+        signal
+    }
+
+    #[test]
+    fn real_freq_matches_complex() {
+        for length in [16, 30, 64] {
+            let mut signal = real_signal(length);
+            let mut expected = vec![Complex::default(); length];
+
+            do_fft(
+                &signal.fft_integer,
+                &signal.time,
+                &mut expected,
+                &mut signal.scratch,
+            );
+            signal.update_freq();
+
+            for (e, r) in expected.iter().zip(signal.freq.iter()) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+            for (k, r) in signal.freq_half.iter().enumerate() {
+                let e = expected[(k + length / 2) % length];
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn time_freq_roundtrip() {
+        let lengths = (2..=130).chain([255, 256, 511, 512, 1000, 1024]);
+
+        for length in lengths {
+            let mut signal = Signal::new(length);
+
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                *v = Complex::new((i as f32 * 0.73).sin(), (i as f32 * 0.29).cos());
+            }
+
+            let expected = signal.time.clone();
+
+            signal.update_freq();
+            signal.time.fill(Complex::default());
+            signal.update_time();
+
+            for (e, r) in expected.iter().zip(signal.time.iter()) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn time_freq_centered() {
+        for length in [7, 8] {
+            let mut signal = Signal::new(length);
+
+            signal.time[length / 2] = Complex::new(1.0, 0.0);
+            signal.update_freq();
+
+            for bin in &signal.freq {
+                assert_approx_eq!(1.0, bin.re, 1e-6);
+                assert_approx_eq!(0.0, bin.im, 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn set_len_pads_centered() {
+        let mut signal = Signal::new(8);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            *v = Complex::new(i as f32, 0.0);
+        }
+
+        let generation = memory_generation();
+        signal.set_len(12, false);
+
+        assert_ne!(generation, memory_generation());
+        assert_eq!(12, signal.get_len());
+        assert_eq!(12, signal.freq.len());
+        assert_eq!(12, signal.frac.len());
+
+        let expected = [0., 0., 0., 1., 2., 3., 4., 5., 6., 7., 0., 0.];
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e, r.re, 1e-6);
+        }
+
+        assert!(signal.set_len(5, false));
+
+        let expected = [2., 3., 4., 5., 6.];
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e, r.re, 1e-6);
+        }
+
+        // too short for the transforms
+        assert!(!signal.set_len(0, false));
+        assert!(!signal.set_len(1, true));
+        assert_eq!(5, signal.get_len());
+    }
+
+    #[test]
+    fn growing_outputs_invalidate_views() {
+        let mut signal = real_signal(32);
+        let mut generation = memory_generation();
+        let mut assert_bumped = || {
+            let current = memory_generation();
+            assert_ne!(generation, current);
+            generation = current;
         };
 
-        for v in self.frac.iter_mut() {
-            *v *= scale;
+        signal.update_zoom(-2.0, 2.0, 64);
+        assert_bumped();
+        signal.update_spectrum(4);
+        assert_bumped();
+        signal.update_radon_wigner(8, false);
+        assert_bumped();
+        signal.update_hermite(8);
+        assert_bumped();
+        assert!(signal.update_psd_periodogram(Window::Hann, 0.95));
+        assert_bumped();
+    }
+
+    #[test]
+    fn set_len_resamples() {
+        let wave = |length: usize, i: usize| {
+            let t = (i as f32 - (length / 2) as f32) / length as f32;
+            Complex::new(
+                (2.0 * PI * 3.0 * t).cos() + 0.5 * (2.0 * PI * 5.0 * t).sin(),
+                0.0,
+            )
+        };
+
+        for (from, to) in [(32, 48), (32, 45), (48, 32), (45, 20), (20, 45)] {
+            let mut signal = Signal::new(from);
+
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                *v = wave(from, i);
+            }
+
+            signal.update_zoom(-2.0, 2.0, 16);
+            signal.set_len(to, true);
+
+            assert_eq!(16, signal.get_zoom_len());
+            for (i, r) in signal.time.iter().enumerate() {
+                let e = wave(to, i);
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+
+            signal.update_freq();
+            signal.update_frac(0.5);
+        }
+    }
+
+    #[test]
+    fn axes_and_peaks() {
+        let (length, rate) = (64, 1000.0);
+        let mut signal = Signal::new(length);
+        assert!(signal.set_sample_rate(rate));
+        signal.set_start_time(0.5);
+
+        // invalid rates are ignored
+        for invalid in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(!signal.set_sample_rate(invalid));
+        }
+        assert_eq!(rate, signal.get_sample_rate());
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            let x = i as f32 - 40.0;
+            *v = Complex::new(0.0, 2.0 * PI * 125.0 * i as f32 / rate).exp()
+                * f32::exp(-x * x / 50.0);
+        }
+
+        assert_approx_eq!(0.5, signal.time_axis[0], 1e-6);
+        assert_approx_eq!(0.5 + 63.0 / rate, signal.time_axis[63], 1e-6);
+        assert_approx_eq!(-500.0, signal.freq_axis[0], 1e-3);
+        assert_approx_eq!(0.0, signal.freq_axis[32], 1e-6);
+        assert_approx_eq!(-0.032, signal.frac_axis[0], 1e-6);
+        assert_approx_eq!(0.54, signal.time_peak(), 1e-4);
+
+        signal.update_freq();
+        assert_approx_eq!(125.0, signal.freq_peak(), 1.0);
+
+        // at order 1 the coordinate is f * s^2 with s^2 = N / rate^2
+        signal.update_frac(1.0);
+        assert_approx_eq!(125.0 * length as f32 / (rate * rate), signal.frac_peak(), 2e-5);
+
+        signal.set_len(128, true);
+        assert_approx_eq!(2000.0, signal.get_sample_rate(), 1e-3);
+        assert_approx_eq!(0.5, signal.get_start_time(), 1e-6);
+        assert_approx_eq!(0.54, signal.time_peak(), 1e-3);
+    }
+
+    #[test]
+    fn cosine_peaks() {
+        let (length, rate, freq) = (256, 8000.0, 1234.5);
+        let mut signal = Signal::new(length);
+        signal.set_sample_rate(rate);
+        signal.set_window(Window::Hann);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            *v = Complex::new(0.8 * (2.0 * PI * freq * i as f32 / rate).cos(), 0.0);
+        }
+
+        signal.update_peaks(4, -60.0, 2.0, Estimator::Gaussian);
+
+        // a real cosine is a pair of complex tones of half the amplitude
+        assert_eq!(2, signal.get_peaks_len());
+        for i in 0..2 {
+            let peak = signal.get_peak(i).unwrap();
+            assert_approx_eq!(freq, peak.frequency.abs(), 1.0);
+            assert_approx_eq!(0.4, peak.amplitude, 4e-3);
+        }
+        let (first, second) = (signal.get_peak(0).unwrap(), signal.get_peak(1).unwrap());
+        assert!(first.frequency * second.frequency < 0.0);
+        assert!(signal.get_peak(2).is_none());
+    }
+
+    #[test]
+    fn psd_of_tone_in_noise() {
+        let (length, rate) = (1024, 2000.0);
+        let mut rng = SplitMix64::new(5);
+        let mut signal = Signal::new(length);
+        signal.set_sample_rate(rate);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            let tone = (2.0 * PI * 250.0 * i as f32 / rate).cos();
+            *v = Complex::new(tone + 0.1 * (rng.next_f32() - 0.5), 0.0);
+        }
+
+        assert!(signal.update_psd_welch(128, 64, Window::Hann, 0.95));
+        assert_eq!(128, signal.get_psd_len());
+        assert_approx_eq!(rate / 128.0, signal.get_psd_resolution(), 1e-4);
+
+        // the tone holds half of the power at each of +-250 Hz
+        let bin = |f: f32| (64.0 + f / signal.get_psd_resolution()) as usize;
+        let power = |psd: &[f32], f: f32| {
+            psd[bin(f) - 2..=bin(f) + 2].iter().sum::<f32>() * signal.get_psd_resolution()
+        };
+        assert_approx_eq!(0.25, power(&signal.psd, 250.0), 0.01);
+        assert_approx_eq!(0.25, power(&signal.psd, -250.0), 0.01);
+
+        for ((d, l), u) in signal.psd.iter().zip(&signal.psd_lower).zip(&signal.psd_upper) {
+            assert!(l < d && d < u);
+        }
+
+        assert!(signal.update_psd_multitaper(4.0, 7, 0.9));
+        assert_eq!(length, signal.get_psd_len());
+        assert_approx_eq!(14.0, signal.get_psd_dof(), 1e-4);
+
+        signal.set_len(512, false);
+        assert_eq!(512, signal.get_psd_len());
+
+        // invalid settings leave the density empty
+        assert!(!signal.update_psd_welch(1024, 0, Window::Hann, 0.95));
+        assert_eq!(0, signal.get_psd_len());
+        assert!(!signal.update_psd_welch(128, 128, Window::Hann, 0.95));
+        assert!(!signal.update_psd_multitaper(4.0, 0, 0.95));
+        assert!(!signal.update_psd_multitaper(f32::NAN, 3, 0.95));
+        assert!(signal.update_psd_periodogram(Window::Hann, 0.95));
+        assert_eq!(512, signal.get_psd_len());
+    }
+
+    #[test]
+    fn noise_at_snr() {
+        let length = 4096;
+        let tone = |i: usize| Complex::new(0.0, i as f32 * 0.3).exp();
+
+        for kind in [NoiseKind::Gaussian, NoiseKind::ComplexGaussian, NoiseKind::Pink] {
+            let mut signal = Signal::new(length);
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                *v = tone(i);
+            }
+
+            signal.add_noise(kind, 17, 20.0);
+            let noise: f32 = (0..length)
+                .map(|i| (signal.time[i] - tone(i)).norm_sqr())
+                .sum::<f32>()
+                / length as f32;
+            assert_approx_eq!(0.01, noise, 1e-5);
+
+            let mut again = Signal::new(length);
+            for (i, v) in again.time.iter_mut().enumerate() {
+                *v = tone(i);
+            }
+            again.add_noise(kind, 17, 20.0);
+            assert_eq!(signal.time, again.time);
+        }
+
+        let mut silent = Signal::new(length);
+        silent.add_noise(NoiseKind::Uniform, 1, 6.0);
+        assert!(is_real(&silent.time));
+        assert_approx_eq!(0.251, noise::power(&silent.time), 0.01);
+    }
+
+    #[test]
+    fn metrics_of_noisy_tone() {
+        let (length, rate) = (2048, 44100.0);
+        let mut signal = Signal::new(length);
+        signal.set_sample_rate(rate);
+        signal.set_window(Window::BlackmanHarris);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            let phase = 2.0 * PI * 3000.0 * i as f32 / rate;
+            *v = Complex::new(phase.sin() + 0.001 * (2.0 * phase).sin(), 0.0);
+        }
+        signal.add_noise(NoiseKind::Gaussian, 8, 50.0);
+
+        let metrics = signal.metrics(5);
+        assert_approx_eq!(3000.0, metrics.fundamental, 1.0);
+        assert_approx_eq!(50.0, metrics.snr, 0.5);
+        assert_approx_eq!(-60.0, metrics.thd, 0.5);
+        assert_approx_eq!(49.6, metrics.sinad, 0.5);
+    }
+
+    #[test]
+    fn frac_dtt_types() {
+        let mut signal = real_signal(16);
+
+        // order 2 of an involution is the identity, and so is order 0
+        // of every type
+        for (kind, order) in [
+            (DttKind::DctIV, 2.0),
+            (DttKind::DctII, 0.0),
+            (DttKind::DstIII, 0.0),
+        ] {
+            signal.update_frac_dtt(kind, order);
+            for (e, r) in signal.time.iter().zip(signal.frac.iter()) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn drpe_roundtrip() {
+        let mut signal = real_signal(64);
+        let expected = signal.time.clone();
+
+        signal.encrypt_drpe(7, 0.6, 1.3);
+        signal.time.fill(Complex::default());
+        signal.decrypt_drpe(7, 0.6, 1.3);
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+
+        // a wrong order, with the transform kept, leaves noise
+        signal.encrypt_drpe(7, 0.6, 1.3);
+        signal.decrypt_drpe(7, 0.6, 1.2);
+        let error: f32 = expected
+            .iter()
+            .zip(signal.time.iter())
+            .map(|(e, r)| (e - r).norm_sqr())
+            .sum();
+        let energy: f32 = expected.iter().map(|e| e.norm_sqr()).sum();
+        assert!(error > 0.1 * energy);
+    }
+
+    #[test]
+    fn real_time_roundtrip() {
+        let mut signal = real_signal(32);
+        let expected = signal.time.clone();
+
+        signal.update_freq_real();
+        signal.time.fill(Complex::default());
+        signal.update_time_real();
+
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+
+        // edits of the full spectrum take effect
+        for (i, v) in signal.freq.iter_mut().enumerate() {
+            if (i as isize - 16).abs() > 4 {
+                *v = Complex::default();
+            }
+        }
+        let mut complex = real_signal(32);
+        complex.freq.copy_from_slice(&signal.freq);
+        complex.update_time();
+        signal.update_time_real();
+
+        for (e, r) in complex.time.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(0.0, r.im, 1e-6);
         }
     }
 }
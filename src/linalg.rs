@@ -0,0 +1,288 @@
+/// Eigen decomposition of a symmetric tridiagonal matrix by the QL
+/// algorithm with implicit shifts.
+///
+/// `diagonal` holds the n diagonal entries and `off` the n-1 entries
+/// coupling row i and i+1. Returns the eigenvalues, unsorted, and the
+/// eigenvectors as columns of a row major n*n matrix.
+pub fn tridiagonal_eigen(diagonal: &[f64], off: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let n = diagonal.len();
+    let mut z = vec![0.0; n * n];
+
+    for i in 0..n {
+        z[i * n + i] = 1.0;
+    }
+
+    (ql_implicit(diagonal, off, Some(&mut z)), z)
+}
+
+/// Eigen decomposition of a dense symmetric matrix, given row major,
+/// by Householder reduction to tridiagonal form and the QL algorithm.
+///
+/// Returns the eigenvalues, unsorted, and the eigenvectors as columns
+/// of a row major n*n matrix.
+pub fn symmetric_eigen(matrix: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    assert_eq!(n * n, matrix.len(), "matrix is not n*n");
+
+    let mut a = matrix.to_vec();
+    let mut q = vec![0.0; n * n];
+    let mut v = vec![0.0; n];
+    let mut w = vec![0.0; n];
+
+    for i in 0..n {
+        q[i * n + i] = 1.0;
+    }
+
+    // reflections H = I - 2vv^T zeroing column k below the subdiagonal,
+    // a <- HaH and q <- qH
+    for k in 0..n.saturating_sub(2) {
+        let norm = (k + 1..n).map(|i| a[i * n + k].powi(2)).sum::<f64>().sqrt();
+        let alpha = -norm.copysign(a[(k + 1) * n + k]);
+
+        v.fill(0.0);
+        for i in k + 1..n {
+            v[i] = a[i * n + k];
+        }
+        v[k + 1] -= alpha;
+
+        let length = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if length <= f64::EPSILON * norm.max(f64::MIN_POSITIVE) {
+            continue;
+        }
+        v.iter_mut().for_each(|x| *x /= length);
+
+        // a - 2vw^T - 2wv^T with w = av - (v^T av) v
+        for (i, w) in w.iter_mut().enumerate() {
+            *w = (0..n).map(|j| a[i * n + j] * v[j]).sum();
+        }
+        let vav: f64 = v.iter().zip(&w).map(|(x, y)| x * y).sum();
+        for (w, x) in w.iter_mut().zip(&v) {
+            *w -= vav * x;
+        }
+        for i in 0..n {
+            for j in 0..n {
+                a[i * n + j] -= 2.0 * (v[i] * w[j] + w[i] * v[j]);
+            }
+        }
+
+        for row in q.chunks_exact_mut(n) {
+            let dot: f64 = row.iter().zip(&v).map(|(x, y)| x * y).sum();
+            for (x, y) in row.iter_mut().zip(&v) {
+                *x -= 2.0 * dot * y;
+            }
+        }
+    }
+
+    let diagonal: Vec<_> = (0..n).map(|i| a[i * n + i]).collect();
+    let off: Vec<_> = (1..n).map(|i| a[i * n + i - 1]).collect();
+    let (values, z) = tridiagonal_eigen(&diagonal, &off);
+
+    // eigenvectors of the original matrix are q z
+    let mut vectors = vec![0.0; n * n];
+    for (row, q) in vectors.chunks_exact_mut(n).zip(q.chunks_exact(n)) {
+        for (m, &x) in q.iter().enumerate() {
+            for (r, y) in row.iter_mut().zip(&z[m * n..(m + 1) * n]) {
+                *r += x * y;
+            }
+        }
+    }
+
+    (values, vectors)
+}
+
+/// Eigenvalues of a symmetric tridiagonal matrix, unsorted, in O(n^2)
+/// instead of the O(n^3) needed for the eigenvectors.
+pub fn tridiagonal_eigenvalues(diagonal: &[f64], off: &[f64]) -> Vec<f64> {
+    ql_implicit(diagonal, off, None)
+}
+
+/// Unit eigenvector of a symmetric tridiagonal matrix for the eigenvalue
+/// `value` by inverse iteration, with an arbitrary sign.
+pub fn tridiagonal_eigenvector(diagonal: &[f64], off: &[f64], value: f64) -> Vec<f64> {
+    let n = diagonal.len();
+    let scale = diagonal
+        .iter()
+        .chain(off)
+        .fold(0.0, |m: f64, v| m.max(v.abs()));
+    let shift = value + scale.max(1.0) * 1e-10;
+
+    // start with all modes present, symmetric ones included
+    let mut x: Vec<f64> = (0..n).map(|i| 1.0 + (i as f64 * 0.618).fract()).collect();
+    let mut c = vec![0.0; n.saturating_sub(1)];
+
+    for _ in 0..3 {
+        // Thomas algorithm for (T - shift) y = x, in place
+        let mut pivot = diagonal[0] - shift;
+        x[0] /= pivot;
+
+        for i in 1..n {
+            c[i - 1] = off[i - 1] / pivot;
+            pivot = diagonal[i] - shift - off[i - 1] * c[i - 1];
+            x[i] = (x[i] - off[i - 1] * x[i - 1]) / pivot;
+        }
+        for i in (0..n - 1).rev() {
+            x[i] -= c[i] * x[i + 1];
+        }
+
+        let norm = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+        x.iter_mut().for_each(|v| *v /= norm);
+    }
+
+    x
+}
+
+/// QL algorithm with implicit shifts, accumulating the rotations
+/// into `z` if given.
+fn ql_implicit(diagonal: &[f64], off: &[f64], mut z: Option<&mut [f64]>) -> Vec<f64> {
+    let n = diagonal.len();
+    let mut d = diagonal.to_vec();
+    let mut e = off.to_vec();
+
+    e.resize(n, 0.0);
+
+    for l in 0..n {
+        let mut iterations = 0;
+
+        loop {
+            let mut m = l;
+            while m + 1 < n {
+                let dd = d[m].abs() + d[m + 1].abs();
+                if e[m].abs() <= f64::EPSILON * dd {
+                    break;
+                }
+                m += 1;
+            }
+
+            if m == l {
+                break;
+            }
+
+            iterations += 1;
+            assert!(
+                iterations < 64,
+                "tridiagonal eigen decomposition does not converge"
+            );
+
+            let mut g = (d[l + 1] - d[l]) / (2.0 * e[l]);
+            let mut r = g.hypot(1.0);
+            g = d[m] - d[l] + e[l] / (g + r.copysign(g));
+
+            let (mut s, mut c, mut p) = (1.0, 1.0, 0.0);
+            let mut underflow = false;
+
+            for i in (l..m).rev() {
+                let f = s * e[i];
+                let b = c * e[i];
+
+                r = f.hypot(g);
+                e[i + 1] = r;
+
+                if r == 0.0 {
+                    d[i + 1] -= p;
+                    e[m] = 0.0;
+                    underflow = true;
+                    break;
+                }
+
+                s = f / r;
+                c = g / r;
+                g = d[i + 1] - p;
+                r = (d[i] - g) * s + 2.0 * c * b;
+                p = s * r;
+                d[i + 1] = g + p;
+                g = c * r - b;
+
+                if let Some(z) = z.as_deref_mut() {
+                    for row in z.chunks_exact_mut(n) {
+                        let f = row[i + 1];
+                        row[i + 1] = s * row[i] + c * f;
+                        row[i] = c * row[i] - s * f;
+                    }
+                }
+            }
+
+            if !underflow {
+                d[l] -= p;
+                e[l] = g;
+                e[m] = 0.0;
+            }
+        }
+    }
+
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg::{
+        symmetric_eigen, tridiagonal_eigen, tridiagonal_eigenvalues, tridiagonal_eigenvector,
+    };
+    use assert_approx_eq::assert_approx_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn second_difference() {
+        let n = 12;
+        let (values, vectors) = tridiagonal_eigen(&vec![2.0; n], &vec![-1.0; n - 1]);
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+
+        for (k, v) in sorted.iter().enumerate() {
+            let expected = 2.0 - 2.0 * f64::cos((k + 1) as f64 * PI / (n + 1) as f64);
+            assert_approx_eq!(expected, v, 1e-12);
+        }
+
+        // columns are orthonormal eigenvectors
+        for (j, value) in values.iter().enumerate() {
+            let column: Vec<_> = (0..n).map(|i| vectors[i * n + j]).collect();
+            let norm: f64 = column.iter().map(|v| v * v).sum();
+            assert_approx_eq!(1.0, norm, 1e-12);
+
+            for i in 0..n {
+                let left = if i > 0 { column[i - 1] } else { 0.0 };
+                let right = if i + 1 < n { column[i + 1] } else { 0.0 };
+                assert_approx_eq!(value * column[i], 2.0 * column[i] - left - right, 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn eigenvalues_and_inverse_iteration() {
+        let n = 40;
+        let diagonal: Vec<f64> = (0..n).map(|i| (i as f64 * 0.3).cos()).collect();
+        let off: Vec<f64> = (1..n).map(|i| 0.5 + 0.01 * i as f64).collect();
+        let (values, vectors) = tridiagonal_eigen(&diagonal, &off);
+
+        assert_eq!(values, tridiagonal_eigenvalues(&diagonal, &off));
+
+        for (j, value) in values.iter().enumerate() {
+            let vector = tridiagonal_eigenvector(&diagonal, &off, *value);
+            let dot: f64 = (0..n).map(|i| vector[i] * vectors[i * n + j]).sum();
+            assert_approx_eq!(1.0, dot.abs(), 1e-9);
+        }
+    }
+
+    #[test]
+    fn dense_symmetric() {
+        let n = 9;
+        let matrix: Vec<f64> = (0..n * n)
+            .map(|ij| {
+                let (i, j) = (ij / n, ij % n);
+                ((i * j) as f64 * 0.7).cos() + (i + j) as f64 * 0.1
+            })
+            .collect();
+        let (values, vectors) = symmetric_eigen(&matrix, n);
+
+        for (k, value) in values.iter().enumerate() {
+            let column: Vec<_> = (0..n).map(|i| vectors[i * n + k]).collect();
+
+            for i in 0..n {
+                let product: f64 = (0..n).map(|j| matrix[i * n + j] * column[j]).sum();
+                assert_approx_eq!(value * column[i], product, 1e-10);
+            }
+            for l in 0..n {
+                let dot: f64 = (0..n).map(|i| column[i] * vectors[i * n + l]).sum();
+                assert_approx_eq!(if k == l { 1.0 } else { 0.0 }, dot, 1e-10);
+            }
+        }
+    }
+}
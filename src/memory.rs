@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use wasm_bindgen::prelude::*;
+
+// Views into the wasm memory (e.g. Float32Arrays over `Signal::get_time`)
+// become stale when the memory grows or when a buffer is reallocated.
+// Reallocations go through `invalidate_views`, growth is noticed by
+// `memory_generation` comparing the memory size, and both bump a global
+// generation counter that the JS side can poll.
+
+static GENERATION: AtomicU32 = AtomicU32::new(0);
+static MEMORY_PAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// Mark all views into the wasm memory as stale.
+pub(crate) fn invalidate_views() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// `Vec::resize` for buffers JS holds views into, marking the views
+/// as stale if the buffer moves.
+pub(crate) fn resize_view<T: Clone>(buffer: &mut Vec<T>, len: usize, value: T) {
+    let before = buffer.as_ptr();
+    buffer.resize(len, value);
+
+    if buffer.as_ptr() != before {
+        invalidate_views();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn memory_pages() -> usize {
+    core::arch::wasm32::memory_size(0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_pages() -> usize {
+    0
+}
+
+/// Counter that changes whenever previously created views into the wasm
+/// memory may have become invalid and have to be recreated.
+#[wasm_bindgen]
+pub fn memory_generation() -> u32 {
+    let pages = memory_pages();
+
+    if MEMORY_PAGES.swap(pages, Ordering::SeqCst) != pages {
+        invalidate_views();
+    }
+
+    GENERATION.load(Ordering::SeqCst)
+}
@@ -0,0 +1,251 @@
+use crate::window::Window;
+use crate::Complex;
+use wasm_bindgen::prelude::*;
+
+/// Quality of a signal made of a single tone, its harmonics and noise,
+/// as used to benchmark converters. Ratios are in dB.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SignalMetrics {
+    /// Frequency of the strongest tone in Hz.
+    pub fundamental: f32,
+    /// Power of the tone over the noise, without harmonics and DC.
+    pub snr: f32,
+    /// Power of the harmonics over the tone, negative for a clean tone
+    /// and minus infinity if the harmonics are below the noise.
+    pub thd: f32,
+    /// Power of the tone over noise and harmonics.
+    pub sinad: f32,
+    /// Power of the tone over the strongest other spectral component.
+    pub sfdr: f32,
+    /// Effective number of bits, (SINAD - 1.76 dB) / 6.02 dB.
+    pub enob: f32,
+}
+
+/// Folds the power of a centered spectrum onto the bins 0 to N/2 of the
+/// non-negative frequencies.
+fn one_sided(spectrum: &[Complex<f32>]) -> Vec<f32> {
+    let n = spectrum.len();
+    let center = n / 2;
+
+    (0..=n / 2)
+        .map(|k| {
+            let positive = spectrum.get(center + k).map_or(0.0, |v| v.norm_sqr());
+            let negative = if k > 0 {
+                spectrum[center - k].norm_sqr()
+            } else {
+                0.0
+            };
+            positive + negative
+        })
+        .collect()
+}
+
+/// Metrics of the centered spectrum `spectrum` of a signal sampled at
+/// `sample_rate` and weighted by `window`, counting `harmonics`
+/// harmonics from the second on.
+///
+/// Negative frequencies are folded onto positive ones. Each tone is
+/// credited with the bins of the main lobe of the window around its peak,
+/// which also keeps leakage out of the noise. The noise density of the
+/// remaining bins stands in for the claimed ones and is taken off the
+/// harmonics. Leakage beyond the main lobe counts as noise, so the
+/// sidelobes of the window have to be below the noise floor, which for
+/// converters takes the Blackman-Harris or flat top window. Harmonics
+/// above half the sample rate are looked for at their aliases.
+///
+/// DC and its main lobe are left out, so the fundamental is the strongest
+/// bin more than a lobe width above DC and closer tones are not found.
+/// Without power around that bin, as for silence, the fundamental is 0
+/// and all ratios are minus infinity.
+pub fn analyze(
+    spectrum: &[Complex<f32>],
+    window: Window,
+    sample_rate: f32,
+    harmonics: usize,
+) -> SignalMetrics {
+    let n = spectrum.len();
+    let power = one_sided(spectrum);
+    let bins = power.len();
+    let lobe = window.main_lobe();
+    let mut used = vec![false; bins];
+
+    // claims the free bins of the lobe around `center`,
+    // returning their power and number
+    let mut claim = |center: usize| -> (f32, usize) {
+        let range = center.saturating_sub(lobe)..(center + lobe + 1).min(bins);
+        range
+            .filter(|&k| !std::mem::replace(&mut used[k], true))
+            .fold((0.0, 0), |(sum, count), k| (sum + power[k], count + 1))
+    };
+
+    claim(0);
+
+    let peak = (lobe + 1..bins)
+        .max_by(|a, b| power[*a].total_cmp(&power[*b]))
+        .unwrap_or(0);
+    let lobe_range = peak.saturating_sub(lobe)..(peak + lobe + 1).min(bins);
+    let lobe_power: f32 = lobe_range.clone().map(|k| power[k]).sum();
+
+    if lobe_power == 0.0 {
+        return SignalMetrics {
+            fundamental: 0.0,
+            snr: f32::NEG_INFINITY,
+            thd: f32::NEG_INFINITY,
+            sinad: f32::NEG_INFINITY,
+            sfdr: f32::NEG_INFINITY,
+            enob: f32::NEG_INFINITY,
+        };
+    }
+
+    let centroid = lobe_range.map(|k| k as f32 * power[k]).sum::<f32>() / lobe_power;
+    let (tone, _) = claim(peak);
+
+    let (mut distortion, mut distortion_bins) = (0.0, 0);
+    for h in 2..harmonics + 2 {
+        // alias of the harmonic into [0, n/2] in fractional bins
+        let position = (centroid * h as f32).rem_euclid(n as f32);
+        let position = position.min(n as f32 - position).round() as usize;
+
+        let search = position.saturating_sub(lobe)..(position + lobe + 1).min(bins);
+        if let Some(harmonic) = search.max_by(|a, b| power[*a].total_cmp(&power[*b])) {
+            let (sum, count) = claim(harmonic);
+            distortion += sum;
+            distortion_bins += count;
+        }
+    }
+
+    // summed directly, the difference to the total is mostly rounding,
+    // and extended over the claimed bins at the same density
+    let free: Vec<f32> = (0..bins).filter(|&k| !used[k]).map(|k| power[k]).collect();
+    let density = free.iter().sum::<f32>() / free.len().max(1) as f32;
+    let noise = (density * bins as f32).max(f32::MIN_POSITIVE);
+    // without the noise under the harmonics
+    let distortion = (distortion - density * distortion_bins as f32).max(0.0);
+    // strongest component of a lobe width clear of DC and the tone
+    let spur = (2 * lobe + 1..bins.saturating_sub(lobe))
+        .filter(|&k| k + 2 * lobe < peak || k > peak + 2 * lobe)
+        .map(|k| power[k - lobe..=k + lobe].iter().sum::<f32>())
+        .fold(f32::MIN_POSITIVE, f32::max);
+
+    let db = |ratio: f32| 10.0 * ratio.log10();
+    let sinad = db(tone / (noise + distortion));
+
+    SignalMetrics {
+        fundamental: centroid * sample_rate / n as f32,
+        snr: db(tone / noise),
+        thd: db(distortion / tone),
+        sinad,
+        sfdr: db(tone / spur),
+        enob: (sinad - 1.76) / 6.02,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::analyze;
+    use crate::noise::{fill, NoiseKind};
+    use crate::plan_cache::plan_fft_forward;
+    use crate::utils::fft_centered;
+    use crate::window::Window;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    fn spectrum(signal: &[f32], window: Window) -> Vec<Complex<f32>> {
+        let mut spectrum: Vec<_> = signal.iter().map(|v| Complex::new(*v, 0.0)).collect();
+        let fft = plan_fft_forward(signal.len());
+        let mut scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        window.apply(&mut spectrum);
+        fft_centered(&fft, &mut spectrum, &mut scratch);
+        spectrum
+    }
+
+    /// Tone of amplitude 1 with harmonics of the given amplitudes.
+    fn distorted(n: usize, freq: f32, harmonics: &[f32]) -> Vec<f32> {
+        (0..n)
+            .map(|i| {
+                let phase = 2.0 * PI * freq * i as f32;
+                phase.cos()
+                    + harmonics
+                        .iter()
+                        .enumerate()
+                        .map(|(h, a)| a * ((h + 2) as f32 * phase + 0.3).cos())
+                        .sum::<f32>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tone_with_harmonics_and_noise() {
+        let (n, rate) = (4096, 48000.0);
+        let freq = 1000.3 / rate;
+        let mut signal = distorted(n, freq, &[0.01, 0.003]);
+
+        // white noise of power 1e-6, 10*log10(0.5 / 1e-6) = 57 dB below the tone
+        let mut noise = vec![Complex::default(); n];
+        fill(NoiseKind::Gaussian, 2, &mut noise);
+        for (v, e) in signal.iter_mut().zip(&noise) {
+            *v += 1e-3 * e.re;
+        }
+
+        for window in [Window::BlackmanHarris, Window::FlatTop] {
+            let metrics = analyze(&spectrum(&signal, window), window, rate, 5);
+            let thd = 10.0 * f32::log10(0.01f32.powi(2) + 0.003f32.powi(2));
+            let sinad = -10.0 * f32::log10(0.01f32.powi(2) + 0.003f32.powi(2) + 2e-6);
+
+            assert_approx_eq!(1000.3, metrics.fundamental, 0.1);
+            assert_approx_eq!(56.99, metrics.snr, 0.3);
+            assert_approx_eq!(thd, metrics.thd, 0.05);
+            assert_approx_eq!(sinad, metrics.sinad, 0.05);
+            assert_approx_eq!(40.0, metrics.sfdr, 0.2);
+            assert_approx_eq!((sinad - 1.76) / 6.02, metrics.enob, 0.01);
+        }
+    }
+
+    #[test]
+    fn aliased_harmonic() {
+        // the third harmonic of 0.3 * rate lands at 0.1 * rate
+        let n = 1024;
+        let signal = distorted(n, 0.3 + 0.17 / n as f32, &[0.0, 0.05]);
+        let window = Window::BlackmanHarris;
+        let metrics = analyze(&spectrum(&signal, window), window, 1.0, 3);
+
+        assert_approx_eq!(20.0 * f32::log10(0.05), metrics.thd, 0.05);
+        assert_approx_eq!(26.02, metrics.sfdr, 0.1);
+    }
+
+    #[test]
+    fn quantized_sine_bits() {
+        let n = 8192;
+
+        for bits in [8, 12] {
+            let step = 2.0 / 2f32.powi(bits);
+            let signal: Vec<f32> = distorted(n, 0.0123, &[])
+                .iter()
+                .map(|v| (v * (1.0 - step / 2.0) / step).round() * step)
+                .collect();
+            let window = Window::BlackmanHarris;
+            let metrics = analyze(&spectrum(&signal, window), window, 1.0, 5);
+
+            assert_approx_eq!(bits as f32, metrics.enob, 0.25);
+        }
+    }
+
+    #[test]
+    fn silence() {
+        let window = Window::BlackmanHarris;
+        let metrics = analyze(&spectrum(&[0.0; 256], window), window, 1.0, 5);
+        let ratios = [
+            metrics.snr,
+            metrics.thd,
+            metrics.sinad,
+            metrics.sfdr,
+            metrics.enob,
+        ];
+
+        assert_eq!(0.0, metrics.fundamental);
+        assert!(ratios.iter().all(|&r| r == f32::NEG_INFINITY));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::do_fft;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::rescale;
+use crate::utils;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::FrftImpl;
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// A signal of several channels of equal length, e.g. stereo recordings
+/// or I/Q pairs.
+///
+/// All channels of a domain are stored back to back in one contiguous
+/// buffer, channel `c` occupies the samples `c*N..(c+1)*N`.
+#[wasm_bindgen]
+pub struct MultiSignal {
+    channels: usize,
+    len: usize,
+    fft_integer: Arc<dyn Fft<f32>>,
+    ifft_integer: Arc<dyn Fft<f32>>,
+    time: Vec<Complex<f32>>,
+    freq: Vec<Complex<f32>>,
+    frac: Vec<Complex<f32>>,
+    combined: Vec<Complex<f32>>,
+    buffer: Vec<Complex<f32>>,
+
+    frft: FrftImpl,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl MultiSignal {
+    fn channel(&self, channel: usize) -> Range<usize> {
+        assert!(channel < self.channels, "channel out of range");
+
+        channel * self.len..(channel + 1) * self.len
+    }
+}
+
+#[wasm_bindgen]
+impl MultiSignal {
+    pub fn new(channels: usize, length: usize) -> Self {
+        utils::set_panic_hook();
+
+        let fft_integer = plan_fft_forward(length);
+        let ifft_integer = plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_integer.get_inplace_scratch_len(),
+            ifft_integer.get_inplace_scratch_len(),
+        );
+
+        Self {
+            channels,
+            len: length,
+            fft_integer,
+            ifft_integer,
+            time: vec![Complex::default(); channels * length],
+            freq: vec![Complex::default(); channels * length],
+            frac: vec![Complex::default(); channels * length],
+            combined: vec![Complex::default(); length],
+            buffer: vec![Complex::default(); length],
+            frft: FrftImpl::new(length),
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    pub fn get_channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get_time(&self, channel: usize) -> *const Complex<f32> {
+        self.time[self.channel(channel)].as_ptr()
+    }
+
+    pub fn get_freq(&self, channel: usize) -> *const Complex<f32> {
+        self.freq[self.channel(channel)].as_ptr()
+    }
+
+    pub fn get_frac(&self, channel: usize) -> *const Complex<f32> {
+        self.frac[self.channel(channel)].as_ptr()
+    }
+
+    /// Result of the last cross channel operation.
+    pub fn get_combined(&self) -> *const Complex<f32> {
+        self.combined.as_ptr()
+    }
+
+    pub fn update_freq(&mut self) {
+        for channel in 0..self.channels {
+            self.update_freq_channel(channel);
+        }
+    }
+
+    pub fn update_freq_channel(&mut self, channel: usize) {
+        let range = self.channel(channel);
+
+        do_fft(
+            &self.fft_integer,
+            &self.time[range.clone()],
+            &mut self.freq[range],
+            &mut self.scratch,
+        );
+    }
+
+    pub fn update_time(&mut self) {
+        for channel in 0..self.channels {
+            self.update_time_channel(channel);
+        }
+    }
+
+    pub fn update_time_channel(&mut self, channel: usize) {
+        let range = self.channel(channel);
+
+        do_fft(
+            &self.ifft_integer,
+            &self.freq[range.clone()],
+            &mut self.time[range],
+            &mut self.scratch,
+        );
+    }
+
+    pub fn update_frac(&mut self, fraction: f32) {
+        for channel in 0..self.channels {
+            self.update_frac_channel(channel, fraction);
+        }
+    }
+
+    pub fn update_frac_channel(&mut self, channel: usize, fraction: f32) {
+        let range = self.channel(channel);
+        let time = &self.time[range.clone()];
+        let frac = &mut self.frac[range];
+
+        frac.copy_from_slice(time);
+        self.frft.process(frac, fraction);
+
+        rescale(time, frac);
+    }
+
+    /// Sum of the time signals of channels `a` and `b`.
+    pub fn sum(&mut self, a: usize, b: usize) {
+        self.combine(a, b, |x, y| x + y);
+    }
+
+    /// Difference of the time signals of channels `a` and `b`.
+    pub fn difference(&mut self, a: usize, b: usize) {
+        self.combine(a, b, |x, y| x - y);
+    }
+
+    /// Cross spectrum X_a * conj(X_b) of the time signals of channel `a`
+    /// and `b`, from their unnormalized centered DFTs.
+    ///
+    /// Unlike the freq views, which are rescaled per channel for display,
+    /// the magnitude keeps the relative levels of the channels.
+    pub fn cross_spectrum(&mut self, a: usize, b: usize) {
+        let (range_a, range_b) = (self.channel(a), self.channel(b));
+
+        self.combined.copy_from_slice(&self.time[range_a]);
+        self.buffer.copy_from_slice(&self.time[range_b]);
+        utils::fft_centered(&self.fft_integer, &mut self.combined, &mut self.scratch);
+        utils::fft_centered(&self.fft_integer, &mut self.buffer, &mut self.scratch);
+
+        for (out, y) in self.combined.iter_mut().zip(&self.buffer) {
+            *out *= y.conj();
+        }
+    }
+}
+
+impl MultiSignal {
+    fn combine(
+        &mut self,
+        a: usize,
+        b: usize,
+        op: impl Fn(Complex<f32>, Complex<f32>) -> Complex<f32>,
+    ) {
+        let (range_a, range_b) = (self.channel(a), self.channel(b));
+
+        for ((out, x), y) in self
+            .combined
+            .iter_mut()
+            .zip(&self.time[range_a])
+            .zip(&self.time[range_b])
+        {
+            *out = op(*x, *y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::multichannel::MultiSignal;
+    use crate::plan_cache::plan_fft_forward;
+    use crate::Complex;
+    use crate::Signal;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    fn channel_value(channel: usize, i: usize) -> Complex<f32> {
+        let x = i as f32 - 16.0;
+        Complex::new(
+            f32::exp(-x * x / (10.0 + channel as f32)),
+            0.1 * channel as f32 * x.sin(),
+        )
+    }
+
+    #[test]
+    fn channels_match_signal() {
+        let mut multi = MultiSignal::new(3, 32);
+
+        for (i, v) in multi.time.iter_mut().enumerate() {
+            *v = channel_value(i / 32, i % 32);
+        }
+
+        multi.update_freq();
+        multi.update_frac(0.6);
+
+        for channel in 0..3 {
+            let mut signal = Signal::new(32);
+
+            for (i, v) in signal.time.iter_mut().enumerate() {
+                *v = channel_value(channel, i);
+            }
+
+            signal.update_freq();
+            signal.update_frac(0.6);
+
+            let range = multi.channel(channel);
+            for (e, r) in signal.freq.iter().zip(&multi.freq[range.clone()]) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+            for (e, r) in signal.frac.iter().zip(&multi.frac[range]) {
+                assert_approx_eq!(e.re, r.re, 1e-4);
+                assert_approx_eq!(e.im, r.im, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn cross_channel_operations() {
+        let mut multi = MultiSignal::new(2, 32);
+
+        for (i, v) in multi.time.iter_mut().enumerate() {
+            *v = channel_value(i / 32, i % 32);
+        }
+
+        multi.difference(1, 0);
+        for (i, r) in multi.combined.iter().enumerate() {
+            let e = channel_value(1, i) - channel_value(0, i);
+            assert_approx_eq!(e.re, r.re, 1e-6);
+            assert_approx_eq!(e.im, r.im, 1e-6);
+        }
+
+        multi.cross_spectrum(1, 1);
+        let mut spectrum = multi.time[32..].to_vec();
+        plan_fft_forward(32).process(&mut spectrum);
+        spectrum.rotate_right(16);
+        for (x, r) in spectrum.iter().zip(multi.combined.iter()) {
+            assert_approx_eq!(x.norm_sqr(), r.re, 1e-3);
+            assert_approx_eq!(0.0, r.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn cross_spectrum_keeps_levels() {
+        // tones of amplitude 1 and 0.5 on bin 3, a quarter turn apart
+        let (n, bin) = (32, 3.0);
+        let mut multi = MultiSignal::new(2, n);
+
+        for (i, v) in multi.time.iter_mut().enumerate() {
+            let (channel, x) = (i / n, (i % n) as f32 - (n / 2) as f32);
+            let phase = 2.0 * PI * bin * x / n as f32;
+            *v = if channel == 0 {
+                Complex::new(0.0, phase).exp()
+            } else {
+                Complex::new(0.0, phase - PI / 2.0).exp() * 0.5
+            };
+        }
+
+        multi.update_freq();
+        multi.cross_spectrum(0, 1);
+
+        // X_0 = N and X_1 = -i * N / 2 at the tone
+        let tone = multi.combined[n / 2 + 3];
+        assert_approx_eq!(0.0, tone.re, 1e-2);
+        assert_approx_eq!((n * n) as f32 / 2.0, tone.im, 1e-2);
+        assert!(multi.combined.iter().map(|v| v.norm()).sum::<f32>() < 1.001 * tone.norm());
+    }
+}
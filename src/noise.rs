@@ -0,0 +1,182 @@
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::rng::SplitMix64;
+use crate::Complex;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// Distribution and spectrum of generated noise. All kinds but
+/// `ComplexGaussian` are real valued.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// White noise uniform in [-sqrt(3), sqrt(3)).
+    Uniform,
+    /// White gaussian noise.
+    Gaussian,
+    /// Circular complex white gaussian noise, half of the
+    /// power in the real and imaginary part each.
+    ComplexGaussian,
+    /// Gaussian noise with a density falling as 1/f.
+    Pink,
+    /// Gaussian noise with a density falling as 1/f^2.
+    Brown,
+}
+
+/// Standard normal samples by the Box-Muller transform.
+fn gaussian_pair(rng: &mut SplitMix64) -> (f32, f32) {
+    let radius = f32::sqrt(-2.0 * (1.0 - rng.next_f32()).ln());
+    let angle = 2.0 * PI * rng.next_f32();
+
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+/// Fill `target` with noise of unit expected power, the same for the
+/// same `seed` on every platform.
+///
+/// Pink and brown noise are shaped in the frequency domain, so they are
+/// periodic in the length of `target` and have no DC component.
+pub fn fill(kind: NoiseKind, seed: u64, target: &mut [Complex<f32>]) {
+    let mut rng = SplitMix64::new(seed);
+
+    for v in target.iter_mut() {
+        *v = match kind {
+            NoiseKind::Uniform => Complex::new(f32::sqrt(3.0) * (2.0 * rng.next_f32() - 1.0), 0.0),
+            NoiseKind::ComplexGaussian => {
+                let (re, im) = gaussian_pair(&mut rng);
+                Complex::new(re, im) * std::f32::consts::FRAC_1_SQRT_2
+            }
+            _ => Complex::new(gaussian_pair(&mut rng).0, 0.0),
+        };
+    }
+
+    let exponent = match kind {
+        NoiseKind::Pink => 0.5,
+        NoiseKind::Brown => 1.0,
+        _ => return,
+    };
+
+    let n = target.len();
+    let fft = plan_fft_forward(n);
+    let ifft = plan_fft_inverse(n);
+    let mut scratch = vec![
+        Complex::default();
+        usize::max(
+            fft.get_inplace_scratch_len(),
+            ifft.get_inplace_scratch_len()
+        )
+    ];
+
+    // the amplitude falls with |f|^-exponent, symmetric so the result stays real
+    fft.process_with_scratch(target, &mut scratch);
+    for (k, v) in target.iter_mut().enumerate() {
+        let f = k.min(n - k);
+        *v *= if f == 0 {
+            0.0
+        } else {
+            (f as f32).powf(-exponent)
+        };
+    }
+    ifft.process_with_scratch(target, &mut scratch);
+
+    let power = target.iter().map(|v| v.re * v.re).sum::<f32>() / n as f32;
+    let scale = if power > 0.0 {
+        power.sqrt().recip()
+    } else {
+        0.0
+    };
+
+    for v in target.iter_mut() {
+        *v = Complex::new(v.re * scale, 0.0);
+    }
+}
+
+/// Mean power of `signal`.
+pub fn power(signal: &[Complex<f32>]) -> f32 {
+    signal.iter().map(|v| v.norm_sqr()).sum::<f32>() / signal.len().max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::{fill, power, NoiseKind};
+    use crate::plan_cache::plan_fft_forward;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+
+    const KINDS: [NoiseKind; 5] = [
+        NoiseKind::Uniform,
+        NoiseKind::Gaussian,
+        NoiseKind::ComplexGaussian,
+        NoiseKind::Pink,
+        NoiseKind::Brown,
+    ];
+
+    fn noise(kind: NoiseKind, seed: u64, n: usize) -> Vec<Complex<f32>> {
+        let mut signal = vec![Complex::default(); n];
+        fill(kind, seed, &mut signal);
+        signal
+    }
+
+    #[test]
+    fn seeded_and_unit_power() {
+        for kind in KINDS {
+            let a = noise(kind, 3, 4096);
+
+            assert_eq!(a, noise(kind, 3, 4096));
+            assert_ne!(a, noise(kind, 4, 4096));
+            assert_approx_eq!(1.0, power(&a), 0.05);
+            assert_eq!(
+                kind == NoiseKind::ComplexGaussian,
+                a.iter().any(|v| v.im != 0.0)
+            );
+        }
+    }
+
+    #[test]
+    fn distributions() {
+        let n = 20000;
+        let uniform = noise(NoiseKind::Uniform, 1, n);
+        assert!(uniform.iter().all(|v| v.re.abs() <= f32::sqrt(3.0)));
+
+        // kurtosis of 3 for gaussian samples
+        let gaussian = noise(NoiseKind::Gaussian, 1, n);
+        let mean = gaussian.iter().map(|v| v.re).sum::<f32>() / n as f32;
+        let fourth = gaussian.iter().map(|v| v.re.powi(4)).sum::<f32>() / n as f32;
+        assert_approx_eq!(0.0, mean, 0.02);
+        assert_approx_eq!(3.0, fourth, 0.15);
+
+        // circular: equal and uncorrelated parts
+        let complex = noise(NoiseKind::ComplexGaussian, 1, n);
+        let re = complex.iter().map(|v| v.re * v.re).sum::<f32>() / n as f32;
+        let im = complex.iter().map(|v| v.im * v.im).sum::<f32>() / n as f32;
+        let cross = complex.iter().map(|v| v.re * v.im).sum::<f32>() / n as f32;
+        assert_approx_eq!(0.5, re, 0.02);
+        assert_approx_eq!(0.5, im, 0.02);
+        assert_approx_eq!(0.0, cross, 0.02);
+    }
+
+    #[test]
+    fn colored_slopes() {
+        let n = 8192;
+        let fft = plan_fft_forward(n);
+
+        // mean density of the bands [16, 32) and [256, 512) differs
+        // by 16 for a density of 1/f and 256 for 1/f^2
+        for (kind, ratio) in [(NoiseKind::Pink, 16.0), (NoiseKind::Brown, 256.0)] {
+            let mut signal = noise(kind, 9, n);
+            fft.process(&mut signal);
+
+            let band = |from: usize| {
+                signal[from..2 * from]
+                    .iter()
+                    .map(|v| v.norm_sqr())
+                    .sum::<f32>()
+                    / from as f32
+            };
+
+            assert_approx_eq!(ratio, band(16) / band(256), 0.3 * ratio);
+
+            // a single sample has no component besides DC
+            assert_eq!(0.0, power(&noise(kind, 9, 1)));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::window::Window;
+use crate::Complex;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// How the position of a peak is refined between bins.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Estimator {
+    /// Parabola through the magnitudes of the peak and its neighbours.
+    Parabolic,
+    /// Parabola through the log magnitudes, exact for a gaussian lobe
+    /// and close to it for the smooth windows.
+    Gaussian,
+    /// Quinn's first estimator from the complex neighbours, exact
+    /// for a single tone under the rectangular window.
+    Quinn,
+}
+
+/// A spectral peak, with amplitude and phase of the complex tone
+/// e^(i*(2*pi*f*t + phase)) it belongs to, the phase relative to
+/// the center sample.
+///
+/// A real cosine of amplitude A shows up as two peaks of amplitude A/2.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Peak {
+    /// Position in (fractional) bins from the center bin.
+    pub bin: f32,
+    /// Frequency in Hz.
+    pub frequency: f32,
+    pub amplitude: f32,
+    pub phase: f32,
+}
+
+/// Settings of the search for the strongest peaks of a spectrum.
+#[derive(Clone, Copy, Debug)]
+pub struct PeakSearch {
+    /// Number of peaks to return at most.
+    pub count: usize,
+    /// Level in dB relative to the strongest bin below which
+    /// peaks are ignored.
+    pub threshold_db: f32,
+    /// Minimum distance in bins between two peaks, weaker peaks
+    /// closer to a stronger one are dropped.
+    pub min_separation: f32,
+    pub estimator: Estimator,
+    /// Window the spectrum was computed with.
+    pub window: Window,
+}
+
+impl PeakSearch {
+    /// The strongest peaks of the centered spectrum `spectrum` of a
+    /// signal sampled at `sample_rate`, sorted by decreasing amplitude.
+    ///
+    /// The spectrum must not be rescaled for the amplitudes to be those
+    /// of the time signal.
+    pub fn find(&self, spectrum: &[Complex<f32>], sample_rate: f32) -> Vec<Peak> {
+        let n = spectrum.len();
+        let magnitude = |i: usize| spectrum[i % n].norm();
+        let strongest = (0..n).map(magnitude).fold(0.0, f32::max);
+        let floor = strongest * 10f32.powf(self.threshold_db / 20.0);
+
+        // local maxima, compared circularly
+        let mut candidates: Vec<usize> = (0..n)
+            .filter(|&i| {
+                let m = magnitude(i);
+                m > 0.0 && m >= floor && m > magnitude(i + n - 1) && m >= magnitude(i + 1)
+            })
+            .collect();
+        candidates.sort_by(|a, b| magnitude(*b).total_cmp(&magnitude(*a)));
+
+        let center = (n / 2) as f32;
+        let mut peaks: Vec<Peak> = Vec::new();
+
+        for i in candidates {
+            if peaks.len() == self.count {
+                break;
+            }
+
+            let bin = i as f32 + self.offset(spectrum, i) - center;
+
+            if peaks
+                .iter()
+                .any(|p| (p.bin - bin).abs() < self.min_separation)
+            {
+                continue;
+            }
+
+            let offset = bin + center - i as f32;
+            let tone = spectrum[i] / self.window.response(n, -offset);
+
+            peaks.push(Peak {
+                bin,
+                frequency: bin * sample_rate / n as f32,
+                amplitude: tone.norm(),
+                phase: tone.arg(),
+            });
+        }
+
+        peaks
+    }
+
+    /// Offset in bins of the true peak from bin `i`, within half a bin.
+    fn offset(&self, spectrum: &[Complex<f32>], i: usize) -> f32 {
+        let n = spectrum.len();
+        let (left, center, right) = (
+            spectrum[(i + n - 1) % n],
+            spectrum[i],
+            spectrum[(i + 1) % n],
+        );
+
+        let parabola = |l: f32, c: f32, r: f32| {
+            let denom = l - 2.0 * c + r;
+            if denom != 0.0 {
+                0.5 * (l - r) / denom
+            } else {
+                0.0
+            }
+        };
+
+        let offset = match self.estimator {
+            Estimator::Parabolic => parabola(left.norm(), center.norm(), right.norm()),
+            Estimator::Gaussian => {
+                if left.norm() > 0.0 && right.norm() > 0.0 {
+                    parabola(left.norm().ln(), center.norm().ln(), right.norm().ln())
+                } else {
+                    0.0
+                }
+            }
+            Estimator::Quinn => {
+                // the neighbours of a centered spectrum carry an extra
+                // phase of e^(+-2*pi*i*c/N) against the plain DFT
+                let turn = Complex::new(0.0, 2.0 * PI * (n / 2) as f32 / n as f32).exp();
+                let alpha_left = (left * turn / center).re;
+                let alpha_right = (right / turn / center).re;
+                let delta_left = alpha_left / (1.0 - alpha_left);
+                let delta_right = -alpha_right / (1.0 - alpha_right);
+
+                if delta_left > 0.0 && delta_right > 0.0 {
+                    delta_right
+                } else {
+                    delta_left
+                }
+            }
+        };
+
+        if offset.is_finite() {
+            offset.clamp(-0.5, 0.5)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::peaks::{Estimator, PeakSearch};
+    use crate::plan_cache::plan_fft_forward;
+    use crate::utils::fft_centered;
+    use crate::window::Window;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    /// Windowed spectrum of the sum of complex tones given
+    /// as (bin, amplitude, phase).
+    fn spectrum(n: usize, tones: &[(f32, f32, f32)], window: Window) -> Vec<Complex<f32>> {
+        let mut signal: Vec<_> = (0..n)
+            .map(|i| {
+                let x = i as f32 - (n / 2) as f32;
+                tones
+                    .iter()
+                    .map(|(bin, a, phase)| {
+                        Complex::from_polar(*a, 2.0 * PI * bin * x / n as f32 + phase)
+                    })
+                    .sum()
+            })
+            .collect();
+        let fft = plan_fft_forward(n);
+        let mut scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        window.apply(&mut signal);
+        fft_centered(&fft, &mut signal, &mut scratch);
+        signal
+    }
+
+    fn search(estimator: Estimator, window: Window) -> PeakSearch {
+        PeakSearch {
+            count: 4,
+            threshold_db: -60.0,
+            min_separation: 2.0,
+            estimator,
+            window,
+        }
+    }
+
+    #[test]
+    fn refines_position_amplitude_and_phase() {
+        // estimator, window and the tolerance of position and amplitude
+        let cases = [
+            (Estimator::Quinn, Window::Rectangular, 1e-3, 1e-3),
+            (Estimator::Parabolic, Window::Hann, 0.06, 0.02),
+            (Estimator::Gaussian, Window::Hann, 0.02, 0.01),
+            (Estimator::Gaussian, Window::BlackmanHarris, 5e-3, 1e-3),
+            (Estimator::Parabolic, Window::FlatTop, 0.15, 1e-3),
+        ];
+
+        for n in [128, 129] {
+            for (estimator, window, position, amplitude) in cases {
+                for bin in [10.0, 10.3, -20.45, 7.5] {
+                    let spectrum = spectrum(n, &[(bin, 0.7, 0.4)], window);
+                    let peaks = search(estimator, window).find(&spectrum, 1000.0);
+                    let frequency = peaks[0].bin * 1000.0 / n as f32;
+
+                    assert_eq!(1, peaks.len());
+                    assert_approx_eq!(bin, peaks[0].bin, position);
+                    assert_approx_eq!(frequency, peaks[0].frequency, 1e-3);
+                    assert_approx_eq!(0.7, peaks[0].amplitude, 0.7 * amplitude);
+                    assert_approx_eq!(0.4, peaks[0].phase, 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn separation_threshold_and_count() {
+        let n = 256;
+        let tones = [
+            (-40.2, 1.0, 0.0),
+            (12.7, 0.5, 1.0),
+            (30.1, 0.01, 0.0),
+            (50.4, 1e-4, 0.0),
+        ];
+        let spectrum = spectrum(n, &tones, Window::BlackmanHarris);
+        let mut search = search(Estimator::Gaussian, Window::BlackmanHarris);
+
+        // the fourth tone is below the threshold
+        let peaks = search.find(&spectrum, n as f32);
+        assert_eq!(3, peaks.len());
+        for (peak, tone) in peaks.iter().zip(&tones) {
+            assert_approx_eq!(tone.0, peak.bin, 0.02);
+            assert_approx_eq!(tone.1, peak.amplitude, 0.02 * tone.1);
+        }
+
+        search.count = 2;
+        assert_eq!(2, search.find(&spectrum, n as f32).len());
+
+        search.count = 4;
+        search.min_separation = 30.0;
+        let peaks = search.find(&spectrum, n as f32);
+        assert_eq!(2, peaks.len());
+        assert_approx_eq!(-40.2, peaks[0].bin, 0.02);
+        assert_approx_eq!(12.7, peaks[1].bin, 0.02);
+    }
+}
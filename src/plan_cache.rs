@@ -0,0 +1,45 @@
+use crate::Arc;
+use crate::Fft;
+use crate::FftPlanner;
+use std::sync::{Mutex, OnceLock};
+
+// A single planner shared by all transforms of the crate.
+// The planner keeps every plan it has created, keyed by length and
+// direction, so constructing many `Signal`s of the same length reuses
+// the plans and their twiddle factors instead of replanning everything.
+static PLANNER: OnceLock<Mutex<FftPlanner<f32>>> = OnceLock::new();
+
+fn with_planner<T>(f: impl FnOnce(&mut FftPlanner<f32>) -> T) -> T {
+    let planner = PLANNER.get_or_init(|| Mutex::new(FftPlanner::new()));
+    let mut planner = planner.lock().unwrap_or_else(|e| e.into_inner());
+
+    f(&mut planner)
+}
+
+pub fn plan_fft_forward(length: usize) -> Arc<dyn Fft<f32>> {
+    with_planner(|planner| planner.plan_fft_forward(length))
+}
+
+pub fn plan_fft_inverse(length: usize) -> Arc<dyn Fft<f32>> {
+    with_planner(|planner| planner.plan_fft_inverse(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+    use crate::Arc;
+    use rustfft::FftDirection;
+
+    #[test]
+    fn plans_are_shared() {
+        let a = plan_fft_forward(48);
+        let b = plan_fft_forward(48);
+        let c = plan_fft_inverse(48);
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(FftDirection::Forward, a.fft_direction());
+        assert_eq!(FftDirection::Inverse, c.fft_direction());
+        assert_eq!(48, c.len());
+    }
+}
@@ -0,0 +1,500 @@
+use crate::linalg::{tridiagonal_eigenvalues, tridiagonal_eigenvector};
+use crate::plan_cache::plan_fft_forward;
+use crate::utils::fft_centered;
+use crate::window::Window;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use std::f64::consts::PI;
+
+/// How the power spectral density is estimated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// A single windowed spectrum of the whole signal.
+    Periodogram { window: Window },
+    /// Average of the windowed spectra of segments of `segment` samples
+    /// overlapping by `overlap` samples.
+    Welch {
+        segment: usize,
+        overlap: usize,
+        window: Window,
+    },
+    /// Average of the spectra of the whole signal under the first `tapers`
+    /// discrete prolate spheroidal sequences of time-bandwidth product
+    /// `bandwidth`, of which about `2 * bandwidth - 1` are well concentrated.
+    Multitaper { bandwidth: f32, tapers: usize },
+}
+
+impl Method {
+    /// Whether the method can estimate a signal of `length` samples,
+    /// with segments that fit the signal and advance, or at least
+    /// one taper of a positive bandwidth.
+    pub fn is_valid(self, length: usize) -> bool {
+        length > 0
+            && match self {
+                Method::Periodogram { .. } => true,
+                Method::Welch {
+                    segment, overlap, ..
+                } => segment <= length && overlap < segment,
+                Method::Multitaper { bandwidth, tapers } => {
+                    tapers > 0 && bandwidth.is_finite() && bandwidth > 0.0
+                }
+            }
+    }
+}
+
+/// Power spectral density estimator.
+///
+/// Densities are two sided and centered like the freq view, in squared
+/// signal units per Hz, so their sum times the bin width is the mean
+/// power of the signal.
+pub struct Psd {
+    method: Method,
+    fft: Arc<dyn Fft<f32>>,
+    /// Tapers of unit energy, each of the segment length.
+    tapers: Vec<Vec<f32>>,
+    hop: usize,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Psd {
+    /// Estimator for signals of `length` samples, which panics
+    /// unless `method.is_valid(length)`.
+    pub fn new(length: usize, method: Method) -> Self {
+        assert!(method.is_valid(length), "invalid estimation method");
+
+        let (segment, hop, tapers) = match method {
+            Method::Periodogram { window } => (length, length, vec![unit_window(window, length)]),
+            Method::Welch {
+                segment,
+                overlap,
+                window,
+            } => (
+                segment,
+                segment - overlap,
+                vec![unit_window(window, segment)],
+            ),
+            Method::Multitaper { bandwidth, tapers } => {
+                (length, length, dpss(length, bandwidth, tapers))
+            }
+        };
+        let fft = plan_fft_forward(segment);
+        let scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        Self {
+            method,
+            fft,
+            tapers,
+            hop,
+            buffer: vec![Complex::default(); segment],
+            scratch,
+        }
+    }
+
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Number of bins of the estimate.
+    pub fn result_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn segments(&self, length: usize) -> usize {
+        (length - self.buffer.len()) / self.hop + 1
+    }
+
+    /// Equivalent degrees of freedom of the chi-squared distribution of
+    /// the estimate for a signal of `length` samples.
+    ///
+    /// Each spectrum adds two, reduced for overlapping segments by the
+    /// correlation of their windows (Percival and Walden, eq. 292b).
+    pub fn degrees_of_freedom(&self, length: usize) -> f32 {
+        let segments = self.segments(length);
+        let taper = &self.tapers[0];
+        let correlation = |shift: usize| -> f32 {
+            taper
+                .iter()
+                .zip(taper.iter().skip(shift))
+                .map(|(a, b)| a * b)
+                .sum()
+        };
+        let overlap: f32 = (1..segments)
+            .map(|m| (1.0 - m as f32 / segments as f32) * correlation(m * self.hop).powi(2))
+            .sum();
+
+        2.0 * (segments * self.tapers.len()) as f32 / (1.0 + 2.0 * overlap)
+    }
+
+    /// Estimate the density of `signal` sampled at `sample_rate`.
+    pub fn process(&mut self, signal: &[Complex<f32>], sample_rate: f32, density: &mut [f32]) {
+        let segment = self.buffer.len();
+        let (segments, hop) = (self.segments(signal.len()), self.hop);
+        let scale = 1.0 / (sample_rate * (segments * self.tapers.len()) as f32);
+
+        density.fill(0.0);
+
+        for start in (0..segments).map(|s| s * hop) {
+            for taper in &self.tapers {
+                let samples = &signal[start..start + segment];
+
+                for ((b, x), w) in self.buffer.iter_mut().zip(samples).zip(taper) {
+                    *b = x * w;
+                }
+
+                fft_centered(&self.fft, &mut self.buffer, &mut self.scratch);
+
+                for (d, b) in density.iter_mut().zip(&self.buffer) {
+                    *d += b.norm_sqr() * scale;
+                }
+            }
+        }
+    }
+}
+
+/// Bounds of the `level` (e.g. 0.95) confidence interval of each
+/// density, which follows a chi-squared distribution with `dof`
+/// degrees of freedom.
+pub fn confidence_bounds(
+    density: &[f32],
+    dof: f32,
+    level: f32,
+    lower: &mut [f32],
+    upper: &mut [f32],
+) {
+    let tail = (1.0 - level as f64) / 2.0;
+    let low = dof as f64 / chi_squared_quantile(dof as f64, 1.0 - tail);
+    let high = dof as f64 / chi_squared_quantile(dof as f64, tail);
+
+    for ((d, l), u) in density.iter().zip(lower).zip(upper) {
+        *l = d * low as f32;
+        *u = d * high as f32;
+    }
+}
+
+/// Natural logarithm of the gamma function by the Lanczos approximation
+/// with g = 7, accurate to about 1e-15.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized lower incomplete gamma function P(a, x), by its series
+/// below a + 1 and by the continued fraction of its complement above
+/// (Numerical Recipes 6.2).
+fn gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        let (mut term, mut sum) = (1.0 / a, 1.0 / a);
+
+        for n in 1..1000 {
+            term *= x / (a + n as f64);
+            sum += term;
+
+            if term.abs() < sum.abs() * f64::EPSILON {
+                break;
+            }
+        }
+
+        sum * prefactor
+    } else {
+        // modified Lentz's method
+        let tiny = f64::MIN_POSITIVE / f64::EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut fraction = d;
+
+        for n in 1..1000 {
+            let an = -(n as f64) * (n as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { tiny } else { d }.recip();
+            c = b + an / c;
+            c = if c.abs() < tiny { tiny } else { c };
+
+            let delta = d * c;
+            fraction *= delta;
+
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+
+        1.0 - fraction * prefactor
+    }
+}
+
+/// Quantile of the chi-squared distribution with `dof` degrees of
+/// freedom, whose distribution function is P(dof/2, x/2), found by
+/// bisection.
+fn chi_squared_quantile(dof: f64, p: f64) -> f64 {
+    if p <= 0.0 {
+        return 0.0;
+    } else if p >= 1.0 {
+        return f64::INFINITY;
+    }
+
+    let cdf = |x: f64| gamma_p(dof / 2.0, x / 2.0);
+    let (mut low, mut high) = (0.0, dof.max(1.0));
+
+    while cdf(high) < p {
+        low = high;
+        high *= 2.0;
+    }
+
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+
+        if cdf(middle) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    0.5 * (low + high)
+}
+
+fn unit_window(window: Window, length: usize) -> Vec<f32> {
+    let mut taper = vec![0.0; length];
+    window.fill(&mut taper);
+
+    let norm = taper.iter().map(|w| w * w).sum::<f32>().sqrt();
+    taper.iter_mut().for_each(|w| *w /= norm);
+    taper
+}
+
+/// The first `count` discrete prolate spheroidal sequences of `length`
+/// samples with time-bandwidth product `bandwidth`, the tapers of unit
+/// energy most concentrated in the band of +-bandwidth/length cycles
+/// per sample.
+///
+/// They are the eigenvectors of a tridiagonal matrix commuting with the
+/// concentration problem, found by inverse iteration, with signs such
+/// that even tapers have a positive sum and odd ones start positive.
+pub fn dpss(length: usize, bandwidth: f32, count: usize) -> Vec<Vec<f32>> {
+    let n = length as f64;
+    let w = bandwidth as f64 / n;
+
+    let diagonal: Vec<f64> = (0..length)
+        .map(|i| ((n - 1.0 - 2.0 * i as f64) / 2.0).powi(2) * (2.0 * PI * w).cos())
+        .collect();
+    let off: Vec<f64> = (1..length)
+        .map(|i| i as f64 * (n - i as f64) / 2.0)
+        .collect();
+    let mut values = tridiagonal_eigenvalues(&diagonal, &off);
+    values.sort_by(|a, b| b.total_cmp(a));
+
+    values
+        .iter()
+        .take(count.min(length))
+        .enumerate()
+        .map(|(k, value)| {
+            let mut taper = tridiagonal_eigenvector(&diagonal, &off, *value);
+            let sign = if k % 2 == 0 {
+                taper.iter().sum::<f64>()
+            } else {
+                // first lobe of the antisymmetric tapers
+                taper.iter().take(length / 2).sum::<f64>()
+            };
+
+            if sign < 0.0 {
+                taper.iter_mut().for_each(|v| *v = -*v);
+            }
+
+            taper.iter().map(|&v| v as f32).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::psd::{chi_squared_quantile, confidence_bounds, dpss, Method, Psd};
+    use crate::rng::SplitMix64;
+    use crate::window::Window;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    fn noise(n: usize, seed: u64) -> Vec<Complex<f32>> {
+        let mut rng = SplitMix64::new(seed);
+        (0..n)
+            .map(|_| Complex::new(rng.next_f32() - 0.5, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn periodogram_parseval() {
+        let signal = noise(100, 7);
+        let rate = 250.0;
+        let mut psd = Psd::new(
+            100,
+            Method::Periodogram {
+                window: Window::Rectangular,
+            },
+        );
+        let mut density = vec![0.0; psd.result_len()];
+        psd.process(&signal, rate, &mut density);
+
+        let power: f32 = signal.iter().map(|v| v.norm_sqr()).sum::<f32>() / 100.0;
+        let integral: f32 = density.iter().sum::<f32>() * rate / 100.0;
+        assert_approx_eq!(power, integral, 1e-5);
+        assert_approx_eq!(2.0, psd.degrees_of_freedom(100), 1e-6);
+    }
+
+    #[test]
+    fn white_noise_level() {
+        // uniform noise of variance 1/12, spread evenly over the sample rate
+        let (n, rate) = (4096, 1000.0);
+        let signal = noise(n, 11);
+        let expected = 1.0 / 12.0 / rate;
+
+        let methods = [
+            Method::Welch {
+                segment: 256,
+                overlap: 128,
+                window: Window::Hann,
+            },
+            Method::Multitaper {
+                bandwidth: 4.0,
+                tapers: 7,
+            },
+        ];
+
+        for method in methods {
+            let mut psd = Psd::new(n, method);
+            let mut density = vec![0.0; psd.result_len()];
+            psd.process(&signal, rate, &mut density);
+
+            let mean = density.iter().sum::<f32>() / density.len() as f32;
+            assert_approx_eq!(expected, mean, 0.03 * expected);
+        }
+
+        // 31 segments of 50% overlapping Hann windows, Percival and Walden eq. 292c
+        let welch = Psd::new(n, methods[0]);
+        let expected = 36.0 * 31.0 * 31.0 / (19.0 * 31.0 - 1.0);
+        assert_approx_eq!(expected, welch.degrees_of_freedom(n), 0.1);
+        assert_approx_eq!(14.0, Psd::new(n, methods[1]).degrees_of_freedom(n), 1e-4);
+    }
+
+    #[test]
+    fn multitaper_tone() {
+        let (n, rate, freq) = (512, 1000.0, 125.0);
+        let signal: Vec<_> = (0..n)
+            .map(|i| Complex::new(0.0, 2.0 * PI * freq * i as f32 / rate).exp() * 0.5)
+            .collect();
+        let mut psd = Psd::new(
+            n,
+            Method::Multitaper {
+                bandwidth: 3.0,
+                tapers: 5,
+            },
+        );
+        let mut density = vec![0.0; n];
+        psd.process(&signal, rate, &mut density);
+
+        let bin = n / 2 + (freq * n as f32 / rate) as usize;
+        let band: f32 = density[bin - 3..=bin + 3].iter().sum::<f32>() * rate / n as f32;
+        let total: f32 = density.iter().sum::<f32>() * rate / n as f32;
+
+        assert_approx_eq!(0.25, total, 1e-4);
+        assert!(band > 0.99 * total);
+        // flat within the band of +-3 bins
+        assert_approx_eq!(density[bin - 1], density[bin + 1], 1e-3 * density[bin]);
+    }
+
+    #[test]
+    fn dpss_tapers() {
+        let (n, bandwidth) = (64, 4.0);
+        let tapers = dpss(n, bandwidth, 7);
+        let w = bandwidth as f64 / n as f64;
+
+        for (k, taper) in tapers.iter().enumerate() {
+            for (l, other) in tapers.iter().enumerate() {
+                let dot: f32 = taper.iter().zip(other).map(|(a, b)| a * b).sum();
+                assert_approx_eq!(if k == l { 1.0 } else { 0.0 }, dot, 1e-5);
+            }
+
+            // even and odd around the center
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            for i in 0..n {
+                assert_approx_eq!(taper[i], sign * taper[n - 1 - i], 1e-5);
+            }
+
+            // share of the energy within the band
+            let concentration: f64 = (0..n)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .map(|(i, j)| {
+                    let kernel = if i == j {
+                        2.0 * w
+                    } else {
+                        let d = i as f64 - j as f64;
+                        (2.0 * std::f64::consts::PI * w * d).sin() / (std::f64::consts::PI * d)
+                    };
+                    taper[i] as f64 * taper[j] as f64 * kernel
+                })
+                .sum();
+            assert!(concentration > if k < 6 { 0.99 } else { 0.9 });
+        }
+
+        assert!(tapers[0].iter().sum::<f32>() > 0.0);
+        assert!(tapers[1][0..n / 2].iter().sum::<f32>() > 0.0);
+    }
+
+    #[test]
+    fn chi_squared_bounds() {
+        // two degrees of freedom have the closed form -2 * ln(1 - p)
+        for p in [0.025, 0.5, 0.975] {
+            let expected = -2.0 * f64::ln(1.0 - p);
+            assert_approx_eq!(expected, chi_squared_quantile(2.0, p), 1e-9 * expected);
+        }
+
+        // tabulated quantiles
+        assert_approx_eq!(5.024, chi_squared_quantile(1.0, 0.975), 1e-3);
+        assert_approx_eq!(20.483, chi_squared_quantile(10.0, 0.975), 1e-3);
+        assert_approx_eq!(3.247, chi_squared_quantile(10.0, 0.025), 1e-3);
+        assert_approx_eq!(34.170, chi_squared_quantile(20.0, 0.975), 1e-3);
+        assert_approx_eq!(9.591, chi_squared_quantile(20.0, 0.025), 1e-3);
+
+        let density = [2.0, 4.0];
+        let (mut lower, mut upper) = ([0.0; 2], [0.0; 2]);
+        confidence_bounds(&density, 10.0, 0.95, &mut lower, &mut upper);
+
+        for i in 0..2 {
+            assert_approx_eq!(density[i] * 10.0 / 20.483, lower[i], 0.01 * density[i]);
+            assert_approx_eq!(density[i] * 10.0 / 3.247, upper[i], 0.01 * density[i]);
+        }
+    }
+}
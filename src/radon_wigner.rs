@@ -0,0 +1,146 @@
+use crate::Complex;
+use crate::FrftChirps;
+use crate::FrftImpl;
+
+/// Squared magnitudes of the fractional Fourier transforms of a signal
+/// for equally spaced orders in [0, 2), the Radon-Wigner transform.
+///
+/// The surface is stored row by row, row `k` holds the order `2k/orders`
+/// and each row has one value per sample, centered like the signal.
+/// Orders in [2, 4) are the mirrored rows and are not computed.
+pub struct RadonWigner {
+    len: usize,
+    chirps: Vec<FrftChirps>,
+    buffer: Vec<Complex<f32>>,
+}
+
+impl RadonWigner {
+    /// Floor of the log scaled surface, relative to its maximum.
+    pub const FLOOR_DB: f32 = -120.0;
+
+    pub fn new(frft: &FrftImpl, length: usize, orders: usize) -> Self {
+        let chirps = (0..orders)
+            .map(|k| frft.unitary_chirps(length, Self::order(orders, k)))
+            .collect();
+
+        Self {
+            len: length,
+            chirps,
+            buffer: vec![Complex::default(); length],
+        }
+    }
+
+    pub fn order(orders: usize, k: usize) -> f32 {
+        2.0 * k as f32 / orders as f32
+    }
+
+    pub fn orders(&self) -> usize {
+        self.chirps.len()
+    }
+
+    pub fn result_len(&self) -> usize {
+        self.len * self.orders()
+    }
+
+    /// Compute the surface of `signal` into `output`. If `log` is set the
+    /// values are in decibel relative to the maximum of the whole surface,
+    /// limited to `FLOOR_DB`.
+    pub fn process(
+        &mut self,
+        frft: &mut FrftImpl,
+        signal: &[Complex<f32>],
+        output: &mut [f32],
+        log: bool,
+    ) {
+        let orders = self.orders();
+
+        for (k, (row, chirps)) in output
+            .chunks_exact_mut(self.len)
+            .zip(&self.chirps)
+            .enumerate()
+        {
+            self.buffer.copy_from_slice(signal);
+            frft.process_unitary_cached(&mut self.buffer, Self::order(orders, k), chirps);
+
+            for (out, v) in row.iter_mut().zip(&self.buffer) {
+                *out = v.norm_sqr();
+            }
+        }
+
+        if log {
+            let max = output.iter().copied().fold(0.0, f32::max);
+            let floor = max * f32::powf(10.0, Self::FLOOR_DB / 10.0);
+
+            for v in output.iter_mut() {
+                *v = if max > 0.0 {
+                    10.0 * f32::log10(v.max(floor) / max)
+                } else {
+                    Self::FLOOR_DB
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::radon_wigner::RadonWigner;
+    use crate::Complex;
+    use crate::FrftImpl;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn rows_match_frft() {
+        let n = 64;
+        let orders = 16;
+        let mut frft = FrftImpl::new(n);
+        let mut surface = RadonWigner::new(&frft, n, orders);
+        let signal: Vec<_> = (0..n)
+            .map(|i| {
+                let x = (i as f32 - 20.0) / 6.0;
+                Complex::new(f32::exp(-x * x), 0.3 * x * f32::exp(-x * x))
+            })
+            .collect();
+        let mut output = vec![0.0; surface.result_len()];
+
+        surface.process(&mut frft, &signal, &mut output, false);
+
+        for (k, row) in output.chunks_exact(n).enumerate() {
+            let mut expected = signal.clone();
+            frft.process_unitary(&mut expected, RadonWigner::order(orders, k));
+
+            for (e, r) in expected.iter().zip(row) {
+                assert_approx_eq!(e.norm_sqr(), r, 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn chirp_concentrates_at_its_order() {
+        let n = 128;
+        let orders = 40;
+        let mut frft = FrftImpl::new(n);
+        let mut surface = RadonWigner::new(&frft, n, orders);
+        // a chirp concentrated at order 1.5: rate -cot(0.75 pi) = 1
+        let signal: Vec<_> = (0..n)
+            .map(|i| {
+                let t = (i as f32 - (n / 2) as f32) / f32::sqrt(n as f32);
+                Complex::new(0.0, PI * t * t).exp()
+            })
+            .collect();
+        let mut output = vec![0.0; surface.result_len()];
+
+        surface.process(&mut frft, &signal, &mut output, true);
+
+        let (peak, max) = output
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+
+        assert_approx_eq!(0.0, max, 1e-6);
+        assert_eq!(30, peak / n);
+        assert!(output.iter().all(|v| *v >= RadonWigner::FLOOR_DB));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use std::f32::consts::PI;
+
+/// FFT of real valued signals of even length N
+/// computed by a complex FFT of length N/2.
+///
+/// The even samples are packed into the real parts and the odd samples
+/// into the imaginary parts, the spectra of both halves are then separated
+/// using the hermitian symmetry and combined with one butterfly step.
+///
+/// Only the non-negative half of the hermitian spectrum,
+/// bins 0 to N/2 (inclusive), is returned.
+pub struct RealFft {
+    len: usize,
+    fft_half: Arc<dyn Fft<f32>>,
+    ifft_half: Arc<dyn Fft<f32>>,
+    twiddles: Vec<Complex<f32>>,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl RealFft {
+    pub fn new(length: usize) -> Self {
+        assert!(length.is_multiple_of(2), "real fft requires an even length");
+
+        let half = length / 2;
+        let twiddles = (0..=half)
+            .map(|k| Complex::new(0.0, -2.0 * PI * k as f32 / length as f32).exp())
+            .collect();
+        let fft_half = plan_fft_forward(half);
+        let ifft_half = plan_fft_inverse(half);
+        let scratch_len = usize::max(
+            fft_half.get_inplace_scratch_len(),
+            ifft_half.get_inplace_scratch_len(),
+        );
+
+        Self {
+            len: length,
+            fft_half,
+            ifft_half,
+            twiddles,
+            buffer: vec![Complex::default(); half],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    pub const fn result_len(length: usize) -> usize {
+        length / 2 + 1
+    }
+
+    /// Forward transform of `input` (length N) into `output` (length N/2+1).
+    pub fn process(&mut self, input: &[f32], output: &mut [Complex<f32>]) {
+        let half = self.len / 2;
+
+        for (z, pair) in self.buffer.iter_mut().zip(input.chunks_exact(2)) {
+            *z = Complex::new(pair[0], pair[1]);
+        }
+
+        self.fft_half
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+        for (k, out) in output.iter_mut().take(half + 1).enumerate() {
+            let z = self.buffer[k % half];
+            let z_mirror = self.buffer[(half - k) % half].conj();
+            let even = (z + z_mirror) * 0.5;
+            let odd = (z - z_mirror) * Complex::new(0.0, -0.5);
+
+            *out = even + self.twiddles[k] * odd;
+        }
+    }
+
+    /// Inverse transform of the half spectrum `input` (length N/2+1) into
+    /// `output` (length N). Like the complex inverse FFT the result is not
+    /// normalized and has to be divided by N.
+    pub fn process_inverse(&mut self, input: &[Complex<f32>], output: &mut [f32]) {
+        let half = self.len / 2;
+
+        for (k, z) in self.buffer.iter_mut().enumerate() {
+            let x = input[k];
+            let x_mirror = input[half - k].conj();
+            let even = (x + x_mirror) * 0.5;
+            let odd = (x - x_mirror) * self.twiddles[k].conj() * 0.5;
+
+            *z = even + Complex::new(0.0, 1.0) * odd;
+        }
+
+        self.ifft_half
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+        for (z, pair) in self.buffer.iter().zip(output.chunks_exact_mut(2)) {
+            pair[0] = 2.0 * z.re;
+            pair[1] = 2.0 * z.im;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::real_fft::RealFft;
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use rustfft::FftPlanner;
+
+    fn signal(n: usize) -> Vec<f32> {
+        (0..n)
+            .map(|i| (i as f32 * 0.41).sin() + 0.3 * (i as f32 * 1.7).cos() - 0.2)
+            .collect()
+    }
+
+    #[test]
+    fn real_fft_matches_complex() {
+        for n in [2, 4, 6, 16, 30, 64] {
+            let x = signal(n);
+            let mut expected: Vec<_> = x.iter().map(|v| Complex::new(*v, 0.0)).collect();
+            FftPlanner::new().plan_fft_forward(n).process(&mut expected);
+
+            let mut rfft = RealFft::new(n);
+            let mut result = vec![Complex::default(); RealFft::result_len(n)];
+            rfft.process(&x, &mut result);
+
+            for (e, r) in expected.iter().zip(result.iter()) {
+                assert_approx_eq!(e.re, r.re, 1e-3);
+                assert_approx_eq!(e.im, r.im, 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn real_fft_roundtrip() {
+        for n in [2, 8, 14, 32] {
+            let x = signal(n);
+            let mut rfft = RealFft::new(n);
+            let mut spectrum = vec![Complex::default(); RealFft::result_len(n)];
+            let mut result = vec![0.0; n];
+
+            rfft.process(&x, &mut spectrum);
+            rfft.process_inverse(&spectrum, &mut result);
+
+            for (e, r) in x.iter().zip(result.iter()) {
+                assert_approx_eq!(e, r / n as f32, 1e-4);
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::czt::ChirpZ;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::utils::fft_centered;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use std::f64::consts::PI;
+
+/// Fractional Fourier transform between two uniform grids of arbitrary
+/// length and sample interval.
+///
+/// Time t is made dimensionless with a `scale` s in the same unit as the
+/// intervals, x = t/s. The transform of order a = 2*alpha/pi is
+///
+/// X(u) = sqrt(1 - i*cot(alpha)) * int x(t) K(t/s, u/s) dt / s
+/// K(x, u) = e^(i*pi*(cot(alpha)*x^2 - 2*csc(alpha)*x*u + cot(alpha)*u^2))
+///
+/// so the output axis u is measured in the unit of the time axis. At order
+/// 1 the sample at u is the Fourier transform at the frequency u/s^2,
+/// divided by s. The transform preserves the energy sum |x|^2 dt.
+///
+/// Input and output are centered at index N/2 and M/2. With an input of N
+/// samples, `scale = interval * sqrt(N)` and equal grids, the result times
+/// sqrt(interval) matches the unitary `Frft` applied to the samples times
+/// sqrt(interval).
+///
+/// Orders with |cot(alpha)| > 1 would need chirps sampled far below their
+/// frequency, so they are reduced to 0.5 <= |a| <= 1.5 by a preceding
+/// Fourier transform on the DFT grid of the input. Like `Frft` this is
+/// only accurate for signals well inside the time-frequency window of the
+/// input grid.
+pub struct ScaledFrft {
+    input_len: usize,
+    output_len: usize,
+    input_interval: f64,
+    output_interval: f64,
+    fft_forward: Arc<dyn Fft<f32>>,
+    fft_inverse: Arc<dyn Fft<f32>>,
+    czt: ChirpZ,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl ScaledFrft {
+    pub fn new(
+        input_len: usize,
+        input_interval: f32,
+        output_len: usize,
+        output_interval: f32,
+        scale: f32,
+    ) -> Self {
+        let fft_forward = plan_fft_forward(input_len);
+        let fft_inverse = plan_fft_inverse(input_len);
+        let scratch_len = usize::max(
+            fft_forward.get_inplace_scratch_len(),
+            fft_inverse.get_inplace_scratch_len(),
+        );
+
+        Self {
+            input_len,
+            output_len,
+            input_interval: input_interval as f64 / scale as f64,
+            output_interval: output_interval as f64 / scale as f64,
+            fft_forward,
+            fft_inverse,
+            czt: ChirpZ::new(input_len, output_len),
+            buffer: vec![Complex::default(); input_len],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    /// The scale for which `length` samples at `interval` form the
+    /// grid of the `Frft`, sqrt(length) samples per unit.
+    pub fn natural_scale(length: usize, interval: f32) -> f32 {
+        interval * f32::sqrt(length as f32)
+    }
+
+    pub fn process(&mut self, input: &[Complex<f32>], output: &mut [Complex<f32>], fraction: f32) {
+        let n = self.input_len;
+        let mut a = (fraction as f64).rem_euclid(4.0);
+        if a > 2.0 {
+            a -= 4.0;
+        }
+
+        self.buffer.copy_from_slice(input);
+        let mut interval = self.input_interval;
+        let mut weight = 1.0;
+
+        // a = (a - 1) + 1 or (a + 1) - 1, by the centered DFT which samples
+        // the Fourier transform at intervals of 1/(N*dx)
+        let pre = if a.abs() < 0.5 || a > 1.5 {
+            Some((&self.fft_forward, 1.0))
+        } else if a < -1.5 {
+            Some((&self.fft_inverse, -1.0))
+        } else {
+            None
+        };
+
+        if let Some((fft, order)) = pre {
+            fft_centered(fft, &mut self.buffer, &mut self.scratch);
+            a -= order;
+            weight = interval;
+            interval = 1.0 / (n as f64 * interval);
+        }
+
+        let alpha = a * PI / 2.0;
+        let (cot, csc) = (alpha.cos() / alpha.sin(), 1.0 / alpha.sin());
+        let amplitude = Complex::new(1.0, -cot).sqrt() * weight * interval;
+        let (center_in, center_out) = ((n / 2) as f64, (self.output_len / 2) as f64);
+        let theta = 2.0 * PI * csc * interval * self.output_interval;
+        let phase = |angle: f64| Complex::new(angle.cos() as f32, angle.sin() as f32);
+
+        for (j, v) in self.buffer.iter_mut().enumerate() {
+            let x = (j as f64 - center_in) * interval;
+            *v *= phase(PI * cot * x * x);
+        }
+
+        // sum_j v_j e^(-i*theta*(j - c)(m - c')) as a chirp-z transform
+        self.czt.process(
+            &self.buffer,
+            output,
+            phase(-theta * center_out),
+            phase(-theta),
+        );
+
+        for (m, v) in output.iter_mut().enumerate() {
+            let u = (m as f64 - center_out) * self.output_interval;
+            let shift = theta * center_in * (m as f64 - center_out);
+            let value = amplitude * Complex::new(0.0, PI * cot * u * u + shift).exp();
+
+            *v *= Complex::new(value.re as f32, value.im as f32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scaled_frft::ScaledFrft;
+    use crate::Complex;
+    use crate::FrftImpl;
+    use assert_approx_eq::assert_approx_eq;
+    use std::f32::consts::PI;
+
+    fn sample(len: usize, interval: f32, f: impl Fn(f32) -> Complex<f32>) -> Vec<Complex<f32>> {
+        (0..len)
+            .map(|j| f((j as f32 - (len / 2) as f32) * interval))
+            .collect()
+    }
+
+    #[test]
+    fn gaussian_is_invariant() {
+        // e^(-pi*x^2) is the order 0 Hermite-Gaussian of the kernel
+        let scale = 2.0;
+        let gaussian = |t: f32| Complex::new(f32::exp(-PI * (t / scale).powi(2)), 0.0);
+        let input = sample(96, 0.1, gaussian);
+
+        for (m, du) in [(64, 0.15), (129, 0.05)] {
+            let mut frft = ScaledFrft::new(96, 0.1, m, du, scale);
+            let mut output = vec![Complex::default(); m];
+
+            for fraction in [0.0, 0.1, 0.5, 1.0, 1.3, 1.9, 2.0, -0.7, 3.4] {
+                frft.process(&input, &mut output, fraction);
+
+                for (e, r) in sample(m, du, gaussian).iter().zip(&output) {
+                    assert_approx_eq!(e.re, r.re, 1e-3);
+                    assert_approx_eq!(e.im, r.im, 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn physical_fourier_transform() {
+        // a tone of 3 Hz in a gaussian window sampled at 50 Hz
+        let (n, rate, freq, width) = (200, 50.0, 3.0, 0.5);
+        let signal = |t: f32| {
+            Complex::new(0.0, 2.0 * PI * freq * t).exp() * f32::exp(-PI * (t / width).powi(2))
+        };
+        let input = sample(n, 1.0 / rate, signal);
+
+        // output every 0.25 Hz, u = f*s^2
+        let scale = 0.7;
+        let df = 0.25;
+        let mut frft = ScaledFrft::new(n, 1.0 / rate, 81, df * scale * scale, scale);
+        let mut output = vec![Complex::default(); 81];
+        frft.process(&input, &mut output, 1.0);
+
+        for (k, r) in output.iter().enumerate() {
+            let f = (k as f32 - 40.0) * df;
+            // fourier transform of the window, shifted to the tone
+            let e = width * f32::exp(-PI * (width * (f - freq)).powi(2));
+            assert_approx_eq!(e / scale, r.re, 1e-3);
+            assert_approx_eq!(0.0, r.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn matches_frft_on_natural_grid() {
+        let n = 64;
+        let interval = 0.02;
+        let scale = ScaledFrft::natural_scale(n, interval);
+        let input = sample(n, interval, |t| {
+            let x = t / scale;
+            Complex::new(0.0, 2.1 * x).exp() * f32::exp(-3.0 * x * x)
+        });
+        let mut frft = ScaledFrft::new(n, interval, n, interval, scale);
+        let mut output = vec![Complex::default(); n];
+
+        for fraction in [0.6, 1.0, 1.4] {
+            let mut expected: Vec<_> = input.iter().map(|v| v * interval.sqrt()).collect();
+            FrftImpl::new(n).process_unitary(&mut expected, fraction);
+            frft.process(&input, &mut output, fraction);
+
+            for (e, r) in expected.iter().zip(&output) {
+                assert_approx_eq!(e.re, r.re * interval.sqrt(), 2e-3);
+                assert_approx_eq!(e.im, r.im * interval.sqrt(), 2e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_between_grids() {
+        let (n, m) = (128, 160);
+        let (dt, du, scale) = (0.05, 0.04, 1.0);
+        let input = sample(n, dt, |t| {
+            Complex::new(0.0, 4.0 * t * t).exp() * f32::exp(-PI * t * t)
+        });
+        let mut forward = ScaledFrft::new(n, dt, m, du, scale);
+        let mut backward = ScaledFrft::new(m, du, n, dt, scale);
+        let mut transformed = vec![Complex::default(); m];
+        let mut result = vec![Complex::default(); n];
+
+        for fraction in [0.3, 0.8, 1.7] {
+            forward.process(&input, &mut transformed, fraction);
+            backward.process(&transformed, &mut result, -fraction);
+
+            let energy_in: f32 = input.iter().map(|v| v.norm_sqr() * dt).sum();
+            let energy_out: f32 = transformed.iter().map(|v| v.norm_sqr() * du).sum();
+            assert_approx_eq!(energy_in, energy_out, 1e-3);
+
+            for (e, r) in input.iter().zip(&result) {
+                assert_approx_eq!(e.re, r.re, 1e-3);
+                assert_approx_eq!(e.im, r.im, 1e-3);
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::sinc::sinc;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use std::f32::consts::PI;
+
+/// Sub-sample shifts of a signal along the time and frequency axes.
+///
+/// `shift_circular` applies the shift theorem by multiplying the spectrum
+/// with a linear phase ramp, so the signal wraps around at the borders.
+/// `delay_linear` convolves with a windowed sinc kernel instead, so samples
+/// shifted out of the buffer are lost and zeros are shifted in.
+pub struct Shifter {
+    fft_forward: Arc<dyn Fft<f32>>,
+    fft_inverse: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Shifter {
+    pub fn new(length: usize) -> Self {
+        let fft_forward = plan_fft_forward(length);
+        let fft_inverse = plan_fft_inverse(length);
+        let scratch_len = usize::max(
+            fft_forward.get_inplace_scratch_len(),
+            fft_inverse.get_inplace_scratch_len(),
+        );
+
+        Self {
+            fft_forward,
+            fft_inverse,
+            buffer: vec![Complex::default(); length],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    /// Circularly shift `signal` towards higher indices by `delay` samples.
+    pub fn shift_circular(&mut self, signal: &mut [Complex<f32>], delay: f32) {
+        let n = signal.len();
+        let f_n = n as f32;
+
+        self.fft_forward
+            .process_with_scratch(signal, &mut self.scratch);
+
+        for (k, bin) in signal.iter_mut().enumerate() {
+            if 2 * k == n {
+                // The nyquist bin belongs to both halves of the spectrum,
+                // averaging both phase ramps keeps real signals real.
+                *bin *= f32::cos(PI * delay);
+            } else {
+                let signed_k = if 2 * k < n { k as f32 } else { k as f32 - f_n };
+                *bin *= Complex::new(0.0, -2.0 * PI * signed_k * delay / f_n).exp();
+            }
+        }
+
+        self.fft_inverse
+            .process_with_scratch(signal, &mut self.scratch);
+
+        for v in signal.iter_mut() {
+            *v /= f_n;
+        }
+    }
+
+    /// Shift `signal` towards higher indices by `delay` samples using a
+    /// Hann windowed sinc filter with `half_width` taps on each side.
+    pub fn delay_linear(&mut self, signal: &mut [Complex<f32>], delay: f32, half_width: usize) {
+        let n = signal.len() as isize;
+        let half_width = half_width.max(1) as isize;
+        let integer = delay.floor();
+        let mu = delay - integer;
+        let integer = integer as isize;
+
+        self.buffer.copy_from_slice(signal);
+
+        let taps = (-half_width + 1)..=half_width;
+        let weight = |j: isize| {
+            let x = j as f32 - mu;
+            let window = 0.5 + 0.5 * f32::cos(PI * x / half_width as f32);
+
+            sinc(x).re * window
+        };
+        let gain: f32 = taps.clone().map(weight).sum();
+
+        for (i, out) in signal.iter_mut().enumerate() {
+            let mut acc = Complex::default();
+
+            for j in taps.clone() {
+                let source = i as isize - integer - j;
+
+                if source >= 0 && source < n {
+                    acc += self.buffer[source as usize] * weight(j);
+                }
+            }
+
+            *out = acc / gain;
+        }
+    }
+}
+
+/// Shift the spectrum of `signal` towards higher frequencies by `bins`
+/// by multiplying with a complex exponential.
+///
+/// The phase of the carrier is zero in the center of the signal,
+/// which is the origin of the time axis.
+pub fn modulate(signal: &mut [Complex<f32>], bins: f32) {
+    let n = signal.len();
+    let f_n = n as f32;
+
+    for (i, v) in signal.iter_mut().enumerate() {
+        let t = i as f32 - (n / 2) as f32;
+        *v *= Complex::new(0.0, 2.0 * PI * bins * t / f_n).exp();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shift::{modulate, Shifter};
+    use crate::Complex;
+    use assert_approx_eq::assert_approx_eq;
+    use rustfft::FftPlanner;
+
+    fn gauss(n: usize, center: f32, width: f32) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| {
+                let x = (i as f32 - center) / width;
+                Complex::new(f32::exp(-x * x), 0.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shift_circular_integer() {
+        let mut shifter = Shifter::new(16);
+        let mut signal = gauss(16, 3.0, 2.0);
+        let mut expected = signal.clone();
+
+        expected.rotate_right(5);
+        shifter.shift_circular(&mut signal, 5.0);
+
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn shift_circular_halves() {
+        let mut shifter = Shifter::new(32);
+        let mut signal = gauss(32, 10.0, 3.0);
+        let mut expected = signal.clone();
+
+        shifter.shift_circular(&mut signal, 0.5);
+        shifter.shift_circular(&mut signal, 0.5);
+        expected.rotate_right(1);
+
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn delay_linear_integer() {
+        let mut shifter = Shifter::new(8);
+        let mut signal: Vec<_> = (1..=8).map(|i| Complex::new(i as f32, 0.0)).collect();
+
+        shifter.delay_linear(&mut signal, 3.0, 4);
+
+        let expected = [0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e, r.re, 1e-4);
+            assert_approx_eq!(0.0, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn delay_linear_fraction() {
+        let mut shifter = Shifter::new(64);
+        let mut signal = gauss(64, 30.0, 6.0);
+        let expected = gauss(64, 32.25, 6.0);
+
+        shifter.delay_linear(&mut signal, 2.25, 16);
+
+        for (e, r) in expected.iter().zip(signal.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-3);
+            assert_approx_eq!(e.im, r.im, 1e-3);
+        }
+    }
+
+    #[test]
+    fn modulate_moves_spectrum() {
+        let mut signal = gauss(32, 16.0, 4.0);
+        let fft = FftPlanner::new().plan_fft_forward(32);
+
+        modulate(&mut signal, 3.0);
+        fft.process(&mut signal);
+
+        let peak = signal
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.norm().partial_cmp(&b.norm()).unwrap())
+            .map(|(i, _)| i)
+            .unwrap();
+
+        assert_eq!(3, peak);
+    }
+}
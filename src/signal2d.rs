@@ -0,0 +1,359 @@
+use crate::drpe::{Drpe2D, DrpeKey};
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+use crate::rescale;
+use crate::utils;
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::FrftImpl;
+use wasm_bindgen::prelude::*;
+
+/// A two dimensional signal, e.g. an image, stored row by row.
+///
+/// All transforms are separable: the 1D transform is applied to every
+/// row and then to every column. Like `Signal`, the origin of every
+/// domain is its center sample (column width/2 of row height/2).
+#[wasm_bindgen]
+pub struct Signal2D {
+    width: usize,
+    height: usize,
+    fft_x: Arc<dyn Fft<f32>>,
+    fft_y: Arc<dyn Fft<f32>>,
+    ifft_x: Arc<dyn Fft<f32>>,
+    ifft_y: Arc<dyn Fft<f32>>,
+    time: Vec<Complex<f32>>,
+    freq: Vec<Complex<f32>>,
+    frac: Vec<Complex<f32>>,
+
+    frft_x: FrftImpl,
+    frft_y: FrftImpl,
+    drpe: Option<Drpe2D>,
+    column: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Axis {
+    X,
+    Y,
+}
+
+/// Apply `transform` to every row (`Axis::X`) and then to every
+/// column (`Axis::Y`) of `data`, using `column` as buffer.
+pub(crate) fn separable(
+    data: &mut [Complex<f32>],
+    width: usize,
+    column: &mut [Complex<f32>],
+    mut transform: impl FnMut(&mut [Complex<f32>], Axis),
+) {
+    for row in data.chunks_exact_mut(width) {
+        transform(row, Axis::X);
+    }
+
+    for x in 0..width {
+        for (c, v) in column.iter_mut().zip(data.iter().skip(x).step_by(width)) {
+            *c = *v;
+        }
+
+        transform(column, Axis::Y);
+
+        for (c, v) in column.iter().zip(data.iter_mut().skip(x).step_by(width)) {
+            *v = *c;
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Signal2D {
+    pub fn new(width: usize, height: usize) -> Self {
+        utils::set_panic_hook();
+
+        let fft_x = plan_fft_forward(width);
+        let fft_y = plan_fft_forward(height);
+        let ifft_x = plan_fft_inverse(width);
+        let ifft_y = plan_fft_inverse(height);
+        let scratch_len = [&fft_x, &fft_y, &ifft_x, &ifft_y]
+            .iter()
+            .map(|fft| fft.get_inplace_scratch_len())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            width,
+            height,
+            fft_x,
+            fft_y,
+            ifft_x,
+            ifft_y,
+            time: vec![Complex::default(); width * height],
+            freq: vec![Complex::default(); width * height],
+            frac: vec![Complex::default(); width * height],
+            frft_x: FrftImpl::new(width),
+            frft_y: FrftImpl::new(height),
+            drpe: None,
+            column: vec![Complex::default(); height],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_len(&self) -> usize {
+        self.time.len()
+    }
+
+    pub fn get_time(&self) -> *const Complex<f32> {
+        self.time.as_ptr()
+    }
+
+    pub fn get_freq(&self) -> *const Complex<f32> {
+        self.freq.as_ptr()
+    }
+
+    pub fn get_frac(&self) -> *const Complex<f32> {
+        self.frac.as_ptr()
+    }
+
+    pub fn update_freq(&mut self) {
+        let scratch = &mut self.scratch;
+        let (fft_x, fft_y) = (&self.fft_x, &self.fft_y);
+
+        self.freq.copy_from_slice(&self.time);
+        separable(
+            &mut self.freq,
+            self.width,
+            &mut self.column,
+            |line, axis| match axis {
+                Axis::X => utils::fft_centered(fft_x, line, scratch),
+                Axis::Y => utils::fft_centered(fft_y, line, scratch),
+            },
+        );
+
+        rescale(&self.time, &mut self.freq);
+    }
+
+    pub fn update_time(&mut self) {
+        let scratch = &mut self.scratch;
+        let (ifft_x, ifft_y) = (&self.ifft_x, &self.ifft_y);
+
+        self.time.copy_from_slice(&self.freq);
+        separable(
+            &mut self.time,
+            self.width,
+            &mut self.column,
+            |line, axis| match axis {
+                Axis::X => utils::fft_centered(ifft_x, line, scratch),
+                Axis::Y => utils::fft_centered(ifft_y, line, scratch),
+            },
+        );
+
+        rescale(&self.freq, &mut self.time);
+    }
+
+    /// Separable fractional Fourier transform with order `fraction_x`
+    /// along the rows and `fraction_y` along the columns.
+    pub fn update_frac(&mut self, fraction_x: f32, fraction_y: f32) {
+        let (frft_x, frft_y) = (&mut self.frft_x, &mut self.frft_y);
+
+        self.frac.copy_from_slice(&self.time);
+        separable(
+            &mut self.frac,
+            self.width,
+            &mut self.column,
+            |line, axis| match axis {
+                Axis::X => frft_x.process_unitary(line, fraction_x),
+                Axis::Y => frft_y.process_unitary(line, fraction_y),
+            },
+        );
+
+        rescale(&self.time, &mut self.frac);
+    }
+
+    /// Encrypt the time signal into frac by double random phase encoding
+    /// with the masks generated from `seed` and the fractional orders
+    /// `order_a` and `order_b`, see `drpe::Drpe2D`.
+    ///
+    /// frac is not rescaled, so that `decrypt_drpe` with the same key
+    /// restores the time signal.
+    pub fn encrypt_drpe(&mut self, seed: u32, order_a: f32, order_b: f32) {
+        self.prepare_drpe(seed, order_a, order_b);
+
+        self.frac.copy_from_slice(&self.time);
+        self.drpe.as_mut().unwrap().encrypt(&mut self.frac);
+    }
+
+    /// Decrypt frac into the time signal, the inverse of `encrypt_drpe`
+    /// if the key matches.
+    pub fn decrypt_drpe(&mut self, seed: u32, order_a: f32, order_b: f32) {
+        self.prepare_drpe(seed, order_a, order_b);
+
+        self.time.copy_from_slice(&self.frac);
+        self.drpe.as_mut().unwrap().decrypt(&mut self.time);
+    }
+}
+
+impl Signal2D {
+    pub fn time(&self) -> &[Complex<f32>] {
+        &self.time
+    }
+
+    pub fn time_mut(&mut self) -> &mut [Complex<f32>] {
+        &mut self.time
+    }
+
+    pub fn freq(&self) -> &[Complex<f32>] {
+        &self.freq
+    }
+
+    pub fn freq_mut(&mut self) -> &mut [Complex<f32>] {
+        &mut self.freq
+    }
+
+    pub fn frac(&self) -> &[Complex<f32>] {
+        &self.frac
+    }
+
+    /// Switch the cached DRPE to the key of `seed`, `order_a` and
+    /// `order_b`, keeping its transforms.
+    fn prepare_drpe(&mut self, seed: u32, order_a: f32, order_b: f32) {
+        let key = DrpeKey {
+            seed: seed as u64,
+            order_a,
+            order_b,
+        };
+
+        match self.drpe.as_mut() {
+            Some(drpe) => drpe.set_key(key),
+            None => self.drpe = Some(Drpe2D::new(self.width, self.height, key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signal2d::Signal2D;
+    use crate::Complex;
+    use crate::FrftImpl;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn profile(n: usize, width: f32, freq: f32) -> Vec<Complex<f32>> {
+        (0..n)
+            .map(|i| {
+                let x = (i as f32 - (n / 2) as f32) / width;
+                Complex::new(0.0, freq * x).exp() * f32::exp(-x * x)
+            })
+            .collect()
+    }
+
+    fn normalized(values: &[Complex<f32>]) -> Vec<Complex<f32>> {
+        let max = values.iter().map(|v| v.norm()).fold(0.0, f32::max);
+
+        values.iter().map(|v| v / max).collect()
+    }
+
+    #[test]
+    fn centered_impulse() {
+        for (width, height) in [(8, 6), (7, 9)] {
+            let mut signal = Signal2D::new(width, height);
+
+            signal.time[(height / 2) * width + width / 2] = Complex::new(1.0, 0.0);
+            signal.update_freq();
+
+            for bin in &signal.freq {
+                assert_approx_eq!(1.0, bin.re, 1e-6);
+                assert_approx_eq!(0.0, bin.im, 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn time_freq_roundtrip() {
+        let (width, height) = (12, 7);
+        let mut signal = Signal2D::new(width, height);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            *v = Complex::new((i as f32 * 0.37).sin(), (i as f32 * 0.13).cos());
+        }
+
+        let expected = signal.time.clone();
+
+        signal.update_freq();
+        signal.time.fill(Complex::default());
+        signal.update_time();
+
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn separable_frac() {
+        let (width, height) = (32, 24);
+        let (fraction_x, fraction_y) = (0.4, 1.3);
+        let u = profile(width, 4.0, 0.5);
+        let v = profile(height, 3.0, -0.8);
+        let mut signal = Signal2D::new(width, height);
+
+        for (y, row) in signal.time.chunks_exact_mut(width).enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                *value = u[x] * v[y];
+            }
+        }
+
+        signal.update_frac(fraction_x, fraction_y);
+
+        let (mut u_frac, mut v_frac) = (u.clone(), v.clone());
+        FrftImpl::new(width).process_unitary(&mut u_frac, fraction_x);
+        FrftImpl::new(height).process_unitary(&mut v_frac, fraction_y);
+
+        let expected: Vec<_> = v_frac
+            .iter()
+            .flat_map(|b| u_frac.iter().map(move |a| a * b))
+            .collect();
+
+        for (e, r) in normalized(&expected)
+            .iter()
+            .zip(normalized(&signal.frac).iter())
+        {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+
+        // order 1 in both directions is the 2D fourier transform
+        signal.update_freq();
+        signal.update_frac(1.0, 1.0);
+
+        for (e, r) in signal.freq.iter().zip(signal.frac.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+
+    #[test]
+    fn drpe_roundtrip() {
+        let (width, height) = (12, 9);
+        let mut signal = Signal2D::new(width, height);
+
+        for (i, v) in signal.time.iter_mut().enumerate() {
+            *v = Complex::new((i as f32 * 0.37).sin(), 0.0);
+        }
+
+        let expected = signal.time.clone();
+
+        signal.encrypt_drpe(3, 0.4, 1.7);
+        signal.time.fill(Complex::default());
+        signal.decrypt_drpe(3, 0.4, 1.7);
+
+        for (e, r) in expected.iter().zip(signal.time.iter()) {
+            assert_approx_eq!(e.re, r.re, 1e-4);
+            assert_approx_eq!(e.im, r.im, 1e-4);
+        }
+    }
+}
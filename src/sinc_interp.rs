@@ -28,7 +28,7 @@ impl Interpolator {
     }
 
     const fn slice_range(length: usize) -> std::ops::Range<usize> {
-        (2 * length - 4)..(Self::conv_length(length) - 2 * length + 2)
+        (2 * length - 3)..(Self::conv_length(length) - 2 * length + 3)
     }
 
     pub const fn result_len(length: usize) -> usize {
@@ -1,3 +1,8 @@
+use crate::Arc;
+use crate::Complex;
+use crate::Fft;
+use crate::plan_cache::{plan_fft_forward, plan_fft_inverse};
+
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
     // `set_panic_hook` function at least once during initialization, and then
@@ -8,3 +13,130 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// Transform a signal whose origin is its center sample (index N/2)
+/// and move the zero frequency bin of the result to the center as well.
+pub fn fft_centered(
+    fft: &Arc<dyn Fft<f32>>,
+    signal: &mut [Complex<f32>],
+    scratch: &mut [Complex<f32>],
+) {
+    let n = signal.len();
+
+    signal.rotate_left(n / 2);
+    fft.process_with_scratch(signal, scratch);
+    signal.rotate_right(n / 2);
+}
+
+/// Order handled by the chirp based algorithms after reducing `fraction`
+/// to 0.5 < a < 1.5 with integer transforms, None for integer orders.
+pub fn reduced_order(fraction: f32) -> Option<f32> {
+    let mut a = (fraction + 4.0).rem_euclid(4.0);
+
+    if a.fract() == 0.0 {
+        return None;
+    }
+
+    if a > 2.0 {
+        a -= 2.0;
+    }
+    if a > 1.5 {
+        a -= 1.0;
+    }
+    if a < 0.5 {
+        a += 1.0;
+    }
+
+    Some(a)
+}
+
+/// Chirps of the general case for one order, precomputed by the
+/// `unitary_chirps` of the FRFT implementation to transform many
+/// signals by the same order.
+pub struct FrftChirps {
+    /// Reduced order the chirps belong to, None for integer orders.
+    pub(crate) order: Option<f32>,
+    pub(crate) chirp_a: Vec<Complex<f32>>,
+    pub(crate) chirp_b: Vec<Complex<f32>>,
+}
+
+/// Mirror a signal at its center sample (index N/2), x[t] -> x[-t].
+pub fn flip_centered(signal: &mut [Complex<f32>]) {
+    let n = signal.len();
+
+    signal.reverse();
+
+    // after reversing, the center of an even length signal is off by one
+    if n.is_multiple_of(2) {
+        signal.rotate_right(1);
+    }
+}
+
+/// Copy a centered signal into a centered buffer of a different length,
+/// zero padding or truncating both ends symmetrically around the origin.
+pub fn pad_centered(source: &[Complex<f32>], target: &mut [Complex<f32>]) {
+    let (n, m) = (source.len() as isize, target.len() as isize);
+
+    target.fill(Complex::default());
+
+    for (i, v) in source.iter().enumerate() {
+        let t = i as isize - n / 2 + m / 2;
+
+        if (0..m).contains(&t) {
+            target[t as usize] = *v;
+        }
+    }
+}
+
+/// Resample a centered signal to the length of `target` by zero padding
+/// or truncating its spectrum, which preserves the band limited signal.
+///
+/// The nyquist bin of even lengths is split between (or folded from) the
+/// two edges of the spectrum so that real signals stay real.
+pub fn resample_centered(source: &[Complex<f32>], target: &mut [Complex<f32>]) {
+    let (n, m) = (source.len(), target.len());
+
+    if n == 0 || m == 0 {
+        target.fill(Complex::default());
+        return;
+    }
+
+    let fft = plan_fft_forward(n);
+    let ifft = plan_fft_inverse(m);
+    let mut scratch = vec![
+        Complex::default();
+        usize::max(
+            fft.get_inplace_scratch_len(),
+            ifft.get_inplace_scratch_len()
+        )
+    ];
+    let mut spectrum = source.to_vec();
+
+    fft_centered(&fft, &mut spectrum, &mut scratch);
+    target.fill(Complex::default());
+
+    for (j, bin) in spectrum.iter().enumerate() {
+        let b = j as isize - (n / 2) as isize;
+        let t = b + (m / 2) as isize;
+
+        if (0..m as isize).contains(&t) {
+            target[t as usize] += bin;
+        } else if m.is_multiple_of(2) && b == (m / 2) as isize {
+            target[0] += bin;
+        }
+    }
+
+    if n.is_multiple_of(2) && m > n {
+        let low = m / 2 - n / 2;
+        let half = target[low] * 0.5;
+
+        target[low] = half;
+        target[m / 2 + n / 2] = half;
+    }
+
+    fft_centered(&ifft, target, &mut scratch);
+
+    for v in target.iter_mut() {
+        *v /= n as f32;
+    }
+}
@@ -0,0 +1,154 @@
+use crate::Complex;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+/// Sum of cosines windows in their periodic form, symmetric around
+/// the center sample N/2 like the other domains.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    /// Four term Blackman-Harris with sidelobes below -92 dB.
+    BlackmanHarris,
+    /// Flat top with an amplitude error below 0.01 dB between bins.
+    FlatTop,
+}
+
+impl Window {
+    pub const ALL: [Window; 6] = [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+        Window::BlackmanHarris,
+        Window::FlatTop,
+    ];
+
+    /// Weights a_k of w(x) = sum_k a_k cos(2*pi*k*x/N), x from the center.
+    fn coefficients(self) -> &'static [f32] {
+        match self {
+            Window::Rectangular => &[1.0],
+            Window::Hann => &[0.5, 0.5],
+            Window::Hamming => &[0.54, 0.46],
+            Window::Blackman => &[0.42, 0.5, 0.08],
+            Window::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+            Window::FlatTop => &[
+                0.215_578_95,
+                0.416_631_58,
+                0.277_263_16,
+                0.083_578_95,
+                0.006_947_368,
+            ],
+        }
+    }
+
+    /// Number of bins on each side of a peak covered by the main lobe.
+    pub fn main_lobe(self) -> usize {
+        self.coefficients().len()
+    }
+
+    pub fn value(self, length: usize, index: usize) -> f32 {
+        let x = 2.0 * PI * (index as f32 - (length / 2) as f32) / length as f32;
+
+        self.coefficients()
+            .iter()
+            .enumerate()
+            .map(|(k, a)| a * (k as f32 * x).cos())
+            .sum()
+    }
+
+    pub fn fill(self, window: &mut [f32]) {
+        let len = window.len();
+
+        for (i, w) in window.iter_mut().enumerate() {
+            *w = self.value(len, i);
+        }
+    }
+
+    pub fn apply(self, signal: &mut [Complex<f32>]) {
+        let len = signal.len();
+
+        for (i, v) in signal.iter_mut().enumerate() {
+            *v *= self.value(len, i);
+        }
+    }
+
+    /// Sum of the weights, the gain for a tone centered on a bin.
+    pub fn coherent_gain(self, length: usize) -> f32 {
+        (0..length).map(|i| self.value(length, i)).sum()
+    }
+
+    /// Equivalent noise bandwidth in bins.
+    pub fn noise_bandwidth(self, length: usize) -> f32 {
+        let power: f32 = (0..length).map(|i| self.value(length, i).powi(2)).sum();
+
+        length as f32 * power / self.coherent_gain(length).powi(2)
+    }
+
+    /// Spectrum of the window `offset` bins from its center, relative to
+    /// the center sample, which is the weight a tone `offset` bins away
+    /// contributes to a bin.
+    pub fn response(self, length: usize, offset: f32) -> Complex<f32> {
+        let center = (length / 2) as f32;
+
+        (0..length)
+            .map(|i| {
+                let angle = -2.0 * PI * offset * (i as f32 - center) / length as f32;
+                self.value(length, i) * Complex::new(0.0, angle).exp()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::window::Window;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn gains_and_bandwidth() {
+        let n = 64;
+
+        assert_approx_eq!(n as f32, Window::Rectangular.coherent_gain(n), 1e-4);
+        assert_approx_eq!(n as f32 / 2.0, Window::Hann.coherent_gain(n), 1e-4);
+        assert_approx_eq!(1.0, Window::Rectangular.noise_bandwidth(n), 1e-5);
+        assert_approx_eq!(1.5, Window::Hann.noise_bandwidth(n), 1e-4);
+        assert_approx_eq!(1.73, Window::Blackman.noise_bandwidth(n), 1e-2);
+        assert_approx_eq!(3.77, Window::FlatTop.noise_bandwidth(n), 1e-2);
+
+        for window in Window::ALL {
+            for length in [15, 16] {
+                let mut w = vec![0.0; length];
+                window.fill(&mut w);
+
+                // periodic and symmetric around the center
+                for i in 1..length {
+                    assert_approx_eq!(w[i], w[(2 * (length / 2) + length - i) % length], 1e-6);
+                }
+                assert_approx_eq!(
+                    window.coherent_gain(length),
+                    window.response(length, 0.0).re,
+                    1e-4
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flat_top_scalloping() {
+        let n = 64;
+        let window = Window::FlatTop;
+        let gain = window.coherent_gain(n);
+
+        for offset in [0.1, 0.25, 0.5] {
+            let loss = window.response(n, offset).norm() / gain;
+            assert_approx_eq!(1.0, loss, 2e-3);
+        }
+
+        let loss = Window::Rectangular.response(n, 0.5).norm() / n as f32;
+        assert_approx_eq!(0.637, loss, 1e-3);
+    }
+}
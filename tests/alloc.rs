@@ -0,0 +1,96 @@
+//! Checks that the per-frame transforms of `Signal` do not allocate.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use fftwasm::Signal;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+
+    ALLOCATIONS.load(Ordering::SeqCst) - before
+}
+
+fn fill(signal: &mut Signal, imaginary: f32) {
+    let len = signal.get_len();
+    let time = signal.get_time() as *mut f32;
+    // SAFETY: the time buffer holds `len` complex values of two f32 each
+    let time = unsafe { std::slice::from_raw_parts_mut(time, 2 * len) };
+
+    for (i, pair) in time.chunks_exact_mut(2).enumerate() {
+        let x = (i as f32 - (len / 2) as f32) / 16.0;
+        pair[0] = f32::exp(-x * x);
+        pair[1] = imaginary * x * f32::exp(-x * x);
+    }
+}
+
+#[test]
+fn transforms_do_not_allocate() {
+    for length in [64, 255, 512] {
+        let mut signal = Signal::new(length);
+
+        for imaginary in [0.0, 1.0] {
+            fill(&mut signal, imaginary);
+            signal.update_radon_wigner(8, false);
+            signal.update_spectrum(4);
+
+            let count = count_allocations(|| {
+                signal.update_freq();
+                signal.update_time();
+                signal.update_freq_real();
+                signal.update_time_real();
+                signal.update_freq_with_cepstrum(0.5);
+                signal.update_analytic();
+                signal.update_radon_wigner(8, true);
+                signal.update_spectrum(4);
+                signal.hilbert_time(0.7, 0.5);
+                signal.hilbert_time(-0.7, 1.0);
+
+                for fraction in [0.0, 0.3, 0.7, 1.0, 1.25, 1.7, 2.0, 2.5, 3.0, 3.5] {
+                    signal.update_frac(fraction);
+                }
+            });
+
+            assert_eq!(0, count, "length {length} allocated {count} times");
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Runs the `fftwasm` command line tool on small input files.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn write_input(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("fftwasm-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn peaks(name: &str, contents: &str) -> Output {
+    let path = write_input(name, contents);
+    let output = Command::new(env!("CARGO_BIN_EXE_fftwasm"))
+        .arg("peaks")
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn peaks_rejects_short_input() {
+    for (name, contents) in [("empty.txt", ""), ("single.txt", "1.0\n")] {
+        let output = peaks(name, contents);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(Some(1), output.status.code(), "{}", stderr);
+        assert!(stderr.contains("need at least 2 samples"), "{}", stderr);
+    }
+}
+
+#[test]
+fn peaks_of_two_samples() {
+    let output = peaks("pair.txt", "0.0\n1.0\n");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("time peak"));
+}